@@ -2,17 +2,13 @@ use eframe::{
     egui::{self, FontDefinitions, FontFamily, Sense, TextStyle, TextureId},
     epi,
 };
-use egui_extras_lib::{
-    asynchron::{Futurized, Progress},
-    Image,
-};
+use egui_extras_lib::{asynchron::Progress, Image, ImageTask, ImageView};
 
 struct MyApp {
     name: String,
     age: u32,
-    counter: u32,
     raw_image: (TextureId, (f32, f32)),
-    image_loader: Option<Futurized<(), Image>>,
+    image_loader: Option<ImageTask>,
     image_clicked: bool,
     btn2_label: String,
 }
@@ -22,7 +18,6 @@ impl Default for MyApp {
         Self {
             name: "Saprol".to_owned(),
             age: 24,
-            counter: 0,
             raw_image: (TextureId::default(), (0.0, 0.0)),
             image_loader: None,
             image_clicked: false,
@@ -42,7 +37,6 @@ impl epi::App for MyApp {
         let Self {
             name,
             age,
-            counter,
             raw_image,
             image_loader,
             image_clicked,
@@ -53,7 +47,6 @@ impl epi::App for MyApp {
             ui.heading("Egui image loader quick demo");
             ui.vertical(|ui| {
                 ui.separator();
-                // ui.label(format!("counter: {}", counter.to_string()));
                 ui.label("Your name: ");
                 ui.text_edit_singleline(name);
             });
@@ -64,8 +57,7 @@ impl epi::App for MyApp {
                     // To terminate the task set to 'None'
                     *image_loader = None;
                     if btn2_label.contains("Loading") {
-                        *btn2_label = "Load SVG".to_string();
-                        *counter = 0
+                        *btn2_label = "Load SVG".to_string()
                     }
                     
                     *age += 1;
@@ -82,8 +74,7 @@ impl epi::App for MyApp {
                 if btn2.clicked() {
                     *image_loader = None;
                     if btn2_label.contains("Loading") {
-                        *btn2_label = "Load SVG".to_string();
-                        *counter = 0
+                        *btn2_label = "Load SVG".to_string()
                     }
 
                     *age += 1;
@@ -99,15 +90,14 @@ impl epi::App for MyApp {
 
             if let Some(task_image_loader) = image_loader {
                 task_image_loader.try_resolve(|progress, _| match progress {
-                    Progress::Current(_) => {
+                    Progress::Current(Some(progress)) => {
                         if task_image_loader.id() == Image::type_id(SVG) {
-                                *counter += 1;
-                                *btn2_label = format!("Loading... {}", counter)
+                                *btn2_label = format!("Loading... {:.0}%", progress.fraction() * 100.0)
                             }
                     }
                     Progress::Completed(_image) => {
                         frame.tex_allocator().free(raw_image.0);
-                        *raw_image = (_image.texture_id(frame), _image.size)
+                        *raw_image = (_image.texture_id(frame), _image.size())
                     }
                     Progress::Error(_image_path) => {
                         println!("unable to load {}", _image_path)
@@ -118,7 +108,6 @@ impl epi::App for MyApp {
                 // restore some states to default
                 if task_image_loader.is_done() {
                     if task_image_loader.id() == Image::type_id(SVG) {
-                        *counter = 0;
                         *btn2_label = "Load SVG".to_string()
                     }
                     *image_loader = None
@@ -134,14 +123,13 @@ impl epi::App for MyApp {
                 });
             }
 
-            // Original image size
-            // let size: (f32, f32) = raw_image.1;
-            // just resize here for smaller image, 0.66x actual size
-            let size: (f32, f32) = (raw_image.1.0/1.5, raw_image.1.1/1.5);
-            
+            // Scaled down to fit (keeping aspect ratio), original size if it's smaller
             let img = ui
-                .image(raw_image.0, size)
-                .interact(Sense::click())
+                .add(
+                    ImageView::new(raw_image.0, raw_image.1)
+                        .max_size([200.0, 200.0])
+                        .sense(Sense::click()),
+                )
                 .on_hover_cursor(egui::CursorIcon::PointingHand)
                 .on_hover_text("This image is clickable!");
             if img.clicked() {
@@ -194,7 +182,7 @@ impl epi::App for MyApp {
         ctx.set_fonts(fonts.clone());
 
         if let Some(_image) = Image::new_from_svg(include_bytes!("../images/heart.svg")) {
-            self.raw_image = (_image.texture_id(frame), _image.size)
+            self.raw_image = (_image.texture_id(frame), _image.size())
         }
     }
 }
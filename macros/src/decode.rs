@@ -0,0 +1,132 @@
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use std::path::Path;
+use syn::{
+    parse::{Parse, ParseStream},
+    LitByteStr, LitInt, LitStr, Token,
+};
+
+/// `"path"` or `"path", width, height`.
+pub struct Input {
+    path: LitStr,
+    size: Option<(u32, u32)>,
+}
+
+impl Input {
+    pub fn path(&self) -> &LitStr {
+        &self.path
+    }
+}
+
+impl Parse for Input {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let path = input.parse()?;
+        let mut size = None;
+        if input.parse::<Option<Token![,]>>()?.is_some() && !input.is_empty() {
+            let width: LitInt = input.parse()?;
+            input.parse::<Token![,]>()?;
+            let height: LitInt = input.parse()?;
+            input.parse::<Option<Token![,]>>()?;
+            size = Some((width.base10_parse()?, height.base10_parse()?));
+        }
+        Ok(Input { path, size })
+    }
+}
+
+pub fn expand(input: Input, path: &Path) -> syn::Result<TokenStream> {
+    let error = |message: String| syn::Error::new(input.path.span(), message);
+    let bytes = std::fs::read(path).map_err(|e| error(format!("{}: {}", e, path.display())))?;
+    let is_svg = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("svg"));
+    let (width, height, rgba) = if is_svg {
+        rasterize(&bytes, input.size)
+    } else {
+        decode(&bytes, input.size)
+    }
+    .ok_or_else(|| error(format!("unable to decode {}", path.display())))?;
+
+    let pixels = LitByteStr::new(&premultiply(rgba), Span::call_site());
+    let path = path.to_string_lossy();
+    let (width, height) = (width as usize, height as usize);
+    Ok(quote! {
+        {
+            // Rebuild when the file changes.
+            const _: &[u8] = include_bytes!(#path);
+            ::egui_extras_lib::StaticImage::new(#width, #height, #pixels)
+        }
+    })
+}
+
+/// Straight alpha RGBA8 of a raster image, scaled down to fit `size` (keeping aspect ratio) if given.
+fn decode(bytes: &[u8], size: Option<(u32, u32)>) -> Option<(u32, u32, Vec<u8>)> {
+    let mut image = image::load_from_memory(bytes).ok()?;
+    if let Some((width, height)) = size {
+        image = image.resize(width, height, image::imageops::FilterType::Triangle)
+    }
+    let image = image.to_rgba8();
+    Some((image.width(), image.height(), image.into_raw()))
+}
+
+/// Straight alpha RGBA8 of a svg, rendered to fit `size` (keeping aspect ratio) or at its own size.
+fn rasterize(bytes: &[u8], size: Option<(u32, u32)>) -> Option<(u32, u32, Vec<u8>)> {
+    let mut options = usvg::Options::default();
+    options.fontdb.load_system_fonts();
+    let tree = usvg::Tree::from_data(bytes, &options.to_ref()).ok()?;
+    let fit_to = match size {
+        Some((width, height)) => usvg::FitTo::Size(width, height),
+        None => usvg::FitTo::Original,
+    };
+    let size = fit_to.fit_to(tree.svg_node().size.to_screen_size())?;
+    let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height())?;
+    resvg::render(
+        &tree,
+        fit_to,
+        tiny_skia::Transform::default(),
+        pixmap.as_mut(),
+    )?;
+    // tiny-skia premultiplies in gamma space, go back to straight alpha first.
+    let mut rgba = Vec::with_capacity(pixmap.data().len());
+    for p in pixmap.pixels() {
+        let c = p.demultiply();
+        rgba.extend_from_slice(&[c.red(), c.green(), c.blue(), c.alpha()])
+    }
+    Some((size.width(), size.height(), rgba))
+}
+
+/// Premultiply in linear space, matching `Color32::from_rgba_unmultiplied` of egui.
+fn premultiply(mut rgba: Vec<u8>) -> Vec<u8> {
+    for p in rgba.chunks_exact_mut(4) {
+        match p[3] {
+            255 => (),
+            0 => p.copy_from_slice(&[0; 4]),
+            a => {
+                let a = a as f32 / 255.0;
+                for c in &mut p[..3] {
+                    *c = gamma_u8_from_linear_f32(linear_f32_from_gamma_u8(*c) * a)
+                }
+            }
+        }
+    }
+    rgba
+}
+
+fn linear_f32_from_gamma_u8(s: u8) -> f32 {
+    if s <= 10 {
+        s as f32 / 3294.6
+    } else {
+        ((s as f32 + 14.025) / 269.025).powf(2.4)
+    }
+}
+
+fn gamma_u8_from_linear_f32(l: f32) -> u8 {
+    if l <= 0.0 {
+        0
+    } else if l <= 0.0031308 {
+        (3294.6 * l).round() as u8
+    } else if l <= 1.0 {
+        (269.025 * l.powf(1.0 / 2.4) - 14.025).round() as u8
+    } else {
+        255
+    }
+}
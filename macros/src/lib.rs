@@ -0,0 +1,86 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use std::path::{Path, PathBuf};
+use syn::{parse_macro_input, LitStr};
+
+#[cfg(feature = "decode")]
+mod decode;
+
+/// File extensions embedded by `include_image!`, anything else in the directory is skipped.
+const EXTENSIONS: &[&str] = &[
+    "png", "jpg", "jpeg", "gif", "bmp", "ico", "tif", "tiff", "webp", "tga", "svg",
+];
+
+/// Embed every image and svg of a directory (relative to the crate root, sub directories included)
+/// into the binary, expands to an `egui_extras_lib::AssetBundle`.
+///
+/// Assets are named by their path relative to the directory with `/` separators, e.g. `"arrows/left.svg"`.
+/// Files are embedded with `include_bytes!` so editing them triggers a rebuild, adding new files doesn't.
+#[proc_macro]
+pub fn include_image(input: TokenStream) -> TokenStream {
+    let dir = parse_macro_input!(input as LitStr);
+    let root = resolve(&dir);
+
+    let mut files = Vec::new();
+    if let Err(e) = collect(&root, &mut files) {
+        let message = format!("unable to read {}: {}", root.display(), e);
+        return syn::Error::new(dir.span(), message)
+            .to_compile_error()
+            .into();
+    }
+    files.sort();
+
+    let assets = files.iter().map(|path| {
+        let name = path
+            .strip_prefix(&root)
+            .unwrap_or(path)
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        let path = path.to_string_lossy();
+        quote! { (#name, include_bytes!(#path) as &'static [u8]) }
+    });
+    let expanded = quote! {
+        ::egui_extras_lib::AssetBundle::new(&[#(#assets),*])
+    };
+    expanded.into()
+}
+
+/// Decode (or rasterize a svg) at compile time into premultiplied pixels,
+/// expands to an `egui_extras_lib::StaticImage` usable in `const` and `static` items.
+///
+/// The path is relative to the crate root, an optional `width, height` scales the image
+/// down (svgs are rendered) to fit that size keeping the aspect ratio:
+/// `include_static_image!("icons/heart.svg", 32, 32)`.
+#[cfg(feature = "decode")]
+#[proc_macro]
+pub fn include_static_image(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as decode::Input);
+    let path = resolve(input.path());
+    decode::expand(input, &path)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+/// Paths given to the macros are relative to the crate being compiled.
+fn resolve(path: &LitStr) -> PathBuf {
+    match std::env::var("CARGO_MANIFEST_DIR") {
+        Ok(manifest_dir) => PathBuf::from(manifest_dir).join(path.value()),
+        Err(_) => PathBuf::from(path.value()),
+    }
+}
+
+fn collect(dir: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect(&path, files)?
+        } else if let Some(ext) = path.extension().and_then(|ext| ext.to_str()) {
+            if EXTENSIONS.iter().any(|e| e.eq_ignore_ascii_case(ext)) {
+                files.push(path)
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,84 @@
+use crate::Image;
+use egui::{TextureId, Vec2};
+use std::collections::HashMap;
+
+/// Named images and svgs embedded into the binary, decoded and uploaded on first use.
+///
+/// Usually made by `include_image!("icons/")` (`macros` feature), or by hand from `include_bytes!`.
+/// Textures are owned by the bundle, call `free` before dropping it.
+pub struct AssetBundle {
+    assets: &'static [(&'static str, &'static [u8])],
+    /// Uploaded textures, `None` for assets which failed to decode so they aren't retried every frame.
+    textures: HashMap<&'static str, Option<(TextureId, Vec2)>>,
+}
+
+impl AssetBundle {
+    /// New bundle from `(name, bytes)` pairs, names ending in `.svg` are rasterized as svg.
+    pub fn new(assets: &'static [(&'static str, &'static [u8])]) -> Self {
+        Self {
+            assets,
+            textures: HashMap::new(),
+        }
+    }
+
+    /// Names of all the assets.
+    pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.assets.iter().map(|(name, _)| *name)
+    }
+
+    pub fn len(&self) -> usize {
+        self.assets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.assets.is_empty()
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.bytes(name).is_some()
+    }
+
+    /// Encoded bytes of an asset.
+    pub fn bytes(&self, name: &str) -> Option<&'static [u8]> {
+        self.assets
+            .iter()
+            .find(|(asset, _)| *asset == name)
+            .map(|(_, bytes)| *bytes)
+    }
+
+    /// Decode an asset, not cached.
+    pub fn image(&self, name: &str) -> Option<Image> {
+        let bytes = self.bytes(name)?;
+        if is_svg(name) {
+            Image::new_from_svg(bytes)
+        } else {
+            Image::new(bytes)
+        }
+    }
+
+    /// Texture id and image size of an asset, decoded and uploaded the first time it's asked for.
+    pub fn texture(&mut self, name: &str, frame: &mut epi::Frame<'_>) -> Option<(TextureId, Vec2)> {
+        let (name, _) = self.assets.iter().find(|(asset, _)| *asset == name)?;
+        if let Some(texture) = self.textures.get(name) {
+            return *texture;
+        }
+        let texture = self
+            .image(name)
+            .map(|image| (image.texture_id(frame), image.size().into()));
+        self.textures.insert(name, texture);
+        texture
+    }
+
+    /// Free every uploaded texture, assets are uploaded again on the next `texture` call.
+    pub fn free(&mut self, frame: &mut epi::Frame<'_>) {
+        for (texture_id, _) in self.textures.drain().filter_map(|(_, texture)| texture) {
+            frame.tex_allocator().free(texture_id)
+        }
+    }
+}
+
+fn is_svg(name: &str) -> bool {
+    name.rsplit('.')
+        .next()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("svg"))
+}
@@ -0,0 +1,261 @@
+use crate::{loader::SVG, Image, ImageLoader, ImageSource, ImageTask, Priority, FULL_UV};
+use asynchron::Progress;
+use egui::{vec2, Color32, Rect, Response, Sense, Shape, Stroke, TextureId, Ui, Vec2};
+use std::f32::consts::TAU;
+
+/// What `AsyncImage` shows while the image is loading.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Placeholder {
+    /// Spinning arc over the background.
+    Spinner,
+    /// Plain color fill.
+    Color(Color32),
+    /// Any already uploaded texture, e.g. a low resolution preview.
+    Texture(TextureId),
+    /// Blurred preview decoded from a BlurHash, e.g. sent along with the image url.
+    BlurHash(String),
+    /// Blurred preview decoded from a ThumbHash.
+    ThumbHash(Vec<u8>),
+}
+
+enum State {
+    Idle,
+    Loading(ImageTask),
+    Ready(TextureId, Vec2),
+    Failed(String),
+}
+
+/// Image widget loading its source in the background, drive it by calling `show` every frame.
+///
+/// Shows a placeholder while loading, an error glyph (error message on hover) on failure,
+/// and requests repaints only while the image is pending.
+/// The texture is owned by the widget, call `free` before dropping it.
+pub struct AsyncImage {
+    source: ImageSource,
+    svg: bool,
+    loader: ImageLoader,
+    priority: Priority,
+    size: Option<Vec2>,
+    placeholder: Placeholder,
+    /// Texture decoded from a hash placeholder, uploaded on first `show`,
+    /// `Some(None)` once decoding failed (or there's no hash) so it isn't retried every frame.
+    placeholder_texture: Option<Option<TextureId>>,
+    sense: Sense,
+    state: State,
+}
+
+impl AsyncImage {
+    /// New image (.png, .gif, .jpg and .etc) from a path or bytes.
+    pub fn new(source: impl Into<ImageSource>) -> Self {
+        Self {
+            source: source.into(),
+            svg: false,
+            loader: ImageLoader::global().clone(),
+            priority: Priority::Normal,
+            size: None,
+            placeholder: Placeholder::Spinner,
+            placeholder_texture: None,
+            sense: Sense::hover(),
+            state: State::Idle,
+        }
+    }
+
+    /// New svg image from a path or bytes.
+    pub fn svg(source: impl Into<ImageSource>) -> Self {
+        Self {
+            svg: true,
+            ..Self::new(source)
+        }
+    }
+
+    /// Size to show the image at, the image size once loaded (and 64x64 before) by default.
+    pub fn size(mut self, size: impl Into<Vec2>) -> Self {
+        self.size = Some(size.into());
+        self
+    }
+
+    /// `Placeholder::Spinner` by default.
+    pub fn placeholder(mut self, placeholder: Placeholder) -> Self {
+        self.placeholder = placeholder;
+        self
+    }
+
+    /// Load on this loader instead of `ImageLoader::global()`.
+    pub fn loader(mut self, loader: ImageLoader) -> Self {
+        self.loader = loader;
+        self
+    }
+
+    pub fn priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Make the widget clickable, `Sense::hover()` by default.
+    pub fn sense(mut self, sense: Sense) -> Self {
+        self.sense = sense;
+        self
+    }
+
+    /// Switch to another source, the current texture is freed and loading starts over on the next `show`.
+    pub fn set_source(&mut self, source: impl Into<ImageSource>, frame: &mut epi::Frame<'_>) {
+        self.free(frame);
+        self.source = source.into();
+    }
+
+    /// Free the textures (if any) and go back to the unloaded state.
+    pub fn free(&mut self, frame: &mut epi::Frame<'_>) {
+        if let State::Ready(texture_id, _) = self.state {
+            frame.tex_allocator().free(texture_id)
+        }
+        self.free_placeholder(frame);
+        self.state = State::Idle
+    }
+
+    fn free_placeholder(&mut self, frame: &mut epi::Frame<'_>) {
+        if let Some(Some(texture_id)) = self.placeholder_texture.take() {
+            frame.tex_allocator().free(texture_id)
+        }
+    }
+
+    /// Texture id and image size once loaded.
+    pub fn texture(&self) -> Option<(TextureId, Vec2)> {
+        match self.state {
+            State::Ready(texture_id, size) => Some((texture_id, size)),
+            _ => None,
+        }
+    }
+
+    pub fn is_loading(&self) -> bool {
+        matches!(self.state, State::Idle | State::Loading(_))
+    }
+
+    /// Why loading failed, if it did.
+    pub fn error(&self) -> Option<&str> {
+        match &self.state {
+            State::Failed(error) => Some(error),
+            _ => None,
+        }
+    }
+
+    /// Retry loading after a failure.
+    pub fn reload(&mut self) {
+        if let State::Failed(_) = self.state {
+            self.state = State::Idle
+        }
+    }
+
+    pub fn show(&mut self, ui: &mut Ui, frame: &mut epi::Frame<'_>) -> Response {
+        self.poll(frame);
+        let size = match (self.size, &self.state) {
+            (Some(size), _) => size,
+            (None, State::Ready(_, size)) => *size,
+            _ => vec2(64.0, 64.0),
+        };
+        let (rect, response) = ui.allocate_exact_size(size, self.sense);
+        match &self.state {
+            State::Ready(texture_id, _) => {
+                ui.painter()
+                    .image(*texture_id, rect, FULL_UV, Color32::WHITE);
+                response
+            }
+            State::Failed(error) => {
+                paint_error(ui, rect);
+                response.on_hover_text(error)
+            }
+            _ => {
+                let placeholder = match self.placeholder_texture(frame) {
+                    Some(texture_id) => Placeholder::Texture(texture_id),
+                    None => self.placeholder.clone(),
+                };
+                paint_placeholder(ui, rect, &placeholder);
+                ui.ctx().request_repaint();
+                response
+            }
+        }
+    }
+
+    /// Decode and upload a hash placeholder once, a hash that fails to decode isn't tried again.
+    fn placeholder_texture(&mut self, frame: &mut epi::Frame<'_>) -> Option<TextureId> {
+        if self.placeholder_texture.is_none() {
+            let preview = match &self.placeholder {
+                Placeholder::BlurHash(hash) => Image::from_blurhash(hash, 32, 32, 1.0),
+                Placeholder::ThumbHash(hash) => Image::from_thumbhash(hash),
+                _ => None,
+            };
+            self.placeholder_texture = Some(preview.map(|preview| preview.texture_id(frame)));
+        }
+        self.placeholder_texture.flatten()
+    }
+
+    pub(crate) fn poll(&mut self, frame: &mut epi::Frame<'_>) {
+        if let State::Idle = self.state {
+            let source = self.source.clone();
+            let task = if self.svg {
+                self.loader.load_svg_with_priority(source, self.priority)
+            } else {
+                self.loader.load_image_with_priority(source, self.priority)
+            };
+            self.state = State::Loading(task)
+        }
+
+        let mut next = None;
+        if let State::Loading(task) = &self.state {
+            task.try_resolve(|progress, _| match progress {
+                Progress::Completed(image) => {
+                    next = Some(State::Ready(image.texture_id(frame), image.size().into()))
+                }
+                Progress::Error(source) => {
+                    let kind = if task.id() == SVG { "svg" } else { "image" };
+                    next = Some(State::Failed(format!("unable to load {} {}", kind, source)))
+                }
+                Progress::Canceled => next = Some(State::Failed("canceled".to_string())),
+                _ => (),
+            })
+        }
+        if let Some(next) = next {
+            // The placeholder isn't shown anymore.
+            self.free_placeholder(frame);
+            self.state = next
+        }
+    }
+}
+
+pub(crate) fn paint_placeholder(ui: &Ui, rect: Rect, placeholder: &Placeholder) {
+    let visuals = ui.visuals();
+    match *placeholder {
+        Placeholder::Color(color) => ui.painter().rect_filled(rect, 0.0, color),
+        Placeholder::Texture(texture_id) => {
+            ui.painter()
+                .image(texture_id, rect, FULL_UV, Color32::WHITE)
+        }
+        // Hashes that failed to decode fall back to the spinner.
+        Placeholder::Spinner | Placeholder::BlurHash(_) | Placeholder::ThumbHash(_) => {
+            ui.painter()
+                .rect_filled(rect, 0.0, visuals.widgets.noninteractive.bg_fill);
+            let radius = (rect.width().min(rect.height()) * 0.25).max(2.0);
+            let start = ui.input().time as f32 * TAU * 0.75;
+            let points = (0..=24)
+                .map(|i| {
+                    let angle = start + i as f32 / 24.0 * TAU * 0.75;
+                    rect.center() + radius * vec2(angle.cos(), angle.sin())
+                })
+                .collect();
+            let stroke = Stroke::new(radius * 0.25, visuals.weak_text_color());
+            ui.painter().add(Shape::line(points, stroke));
+        }
+    }
+}
+
+pub(crate) fn paint_error(ui: &Ui, rect: Rect) {
+    let painter = ui.painter();
+    let color = Color32::from_rgb(220, 50, 50);
+    painter.rect_filled(rect, 0.0, ui.visuals().widgets.noninteractive.bg_fill);
+    let radius = (rect.width().min(rect.height()) * 0.25).max(4.0);
+    let stroke = Stroke::new((radius * 0.2).max(1.0), color);
+    painter.circle_stroke(rect.center(), radius, stroke);
+    let d = radius * 0.45;
+    let c = rect.center();
+    painter.line_segment([c + vec2(-d, -d), c + vec2(d, d)], stroke);
+    painter.line_segment([c + vec2(d, -d), c + vec2(-d, d)], stroke);
+}
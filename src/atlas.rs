@@ -0,0 +1,250 @@
+use crate::Image;
+use egui::{pos2, Color32, Rect, TextureId};
+use std::collections::HashMap;
+
+/// Handle of an image inserted into a `TextureAtlas`, stays valid across repacking.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct AtlasKey(usize);
+
+#[derive(Clone, Copy)]
+struct Slot {
+    page: usize,
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+}
+
+struct Shelf {
+    y: usize,
+    height: usize,
+    /// Next free x.
+    x: usize,
+}
+
+struct Page {
+    image: Image,
+    shelves: Vec<Shelf>,
+    texture: Option<TextureId>,
+    dirty: bool,
+}
+
+impl Page {
+    fn new(width: usize, height: usize) -> Self {
+        Self {
+            image: Image::from_fn(width, height, |_, _| Color32::TRANSPARENT),
+            shelves: Vec::new(),
+            texture: None,
+            dirty: true,
+        }
+    }
+
+    /// Shelf packing: the shortest shelf tall enough with room left, else a new shelf below the last one.
+    fn allocate(&mut self, width: usize, height: usize) -> Option<(usize, usize)> {
+        let page_width = self.image.width();
+        let shelf = self
+            .shelves
+            .iter_mut()
+            .filter(|shelf| shelf.height >= height && page_width - shelf.x >= width)
+            .min_by_key(|shelf| shelf.height);
+        if let Some(shelf) = shelf {
+            let x = shelf.x;
+            shelf.x += width;
+            return Some((x, shelf.y));
+        }
+        let y = self
+            .shelves
+            .last()
+            .map_or(0, |shelf| shelf.y + shelf.height);
+        if width > page_width || y + height > self.image.height() {
+            return None;
+        }
+        self.shelves.push(Shelf {
+            y,
+            height,
+            x: width,
+        });
+        Some((0, y))
+    }
+
+    fn blit(&mut self, x: usize, y: usize, image: &Image) {
+        let page_width = self.image.width();
+        for (row, pixels) in image.rows().enumerate() {
+            let start = (y + row) * page_width + x;
+            self.image.pixels_mut()[start..start + pixels.len()].copy_from_slice(pixels)
+        }
+        self.dirty = true
+    }
+}
+
+/// Packs many small images (e.g. toolbar icons) into a few large textures.
+///
+/// Draw an entry with the texture id and uv rect from `get`, e.g. `egui::Image::new(texture_id, size).uv(uv)`.
+/// Textures are (re)uploaded by `update`, call it once per frame before drawing,
+/// texture ids may change on every insertion so don't keep them around.
+/// Removed images leave holes behind until `repack`.
+/// The textures are owned by the atlas, call `free` before dropping it.
+pub struct TextureAtlas {
+    page_size: usize,
+    padding: usize,
+    pages: Vec<Page>,
+    slots: HashMap<AtlasKey, Slot>,
+    next_key: usize,
+    /// Textures of pages dropped by `repack`, freed by the next `update`.
+    stale: Vec<TextureId>,
+}
+
+impl Default for TextureAtlas {
+    fn default() -> Self {
+        Self::new(1024)
+    }
+}
+
+impl TextureAtlas {
+    /// New atlas with `page_size` x `page_size` pixel textures, images larger than that get a page of their own.
+    pub fn new(page_size: usize) -> Self {
+        Self {
+            page_size: page_size.max(1),
+            padding: 1,
+            pages: Vec::new(),
+            slots: HashMap::new(),
+            next_key: 0,
+            stale: Vec::new(),
+        }
+    }
+
+    /// Transparent pixels kept around every image so neighbours don't bleed in when filtering, 1 by default.
+    pub fn padding(mut self, padding: usize) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Copy an image into the atlas.
+    pub fn insert(&mut self, image: &Image) -> AtlasKey {
+        let key = AtlasKey(self.next_key);
+        self.next_key += 1;
+        let slot = self.place(image);
+        self.slots.insert(key, slot);
+        key
+    }
+
+    /// Forget an image, its space is reclaimed by the next `repack`.
+    pub fn remove(&mut self, key: AtlasKey) -> bool {
+        self.slots.remove(&key).is_some()
+    }
+
+    pub fn contains(&self, key: AtlasKey) -> bool {
+        self.slots.contains_key(&key)
+    }
+
+    /// Number of images in the atlas.
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    /// Number of textures in use.
+    pub fn pages(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Texture id and uv rect of an image, `None` if unknown or not uploaded by `update` yet.
+    pub fn get(&self, key: AtlasKey) -> Option<(TextureId, Rect)> {
+        let slot = self.slots.get(&key)?;
+        let page = &self.pages[slot.page];
+        let texture_id = page.texture?;
+        let (width, height) = page.image.size();
+        let uv = Rect::from_min_max(
+            pos2(slot.x as f32 / width, slot.y as f32 / height),
+            pos2(
+                (slot.x + slot.width) as f32 / width,
+                (slot.y + slot.height) as f32 / height,
+            ),
+        );
+        Some((texture_id, uv))
+    }
+
+    /// Pixel size of an image.
+    pub fn size(&self, key: AtlasKey) -> Option<(f32, f32)> {
+        self.slots
+            .get(&key)
+            .map(|slot| (slot.width as f32, slot.height as f32))
+    }
+
+    /// Pack the remaining images again from scratch (tallest first), dropping the holes left by `remove`.
+    pub fn repack(&mut self) {
+        let old_pages = std::mem::take(&mut self.pages);
+        self.stale
+            .extend(old_pages.iter().filter_map(|page| page.texture));
+        let mut slots: Vec<(AtlasKey, Slot)> = self.slots.drain().collect();
+        slots.sort_by_key(|(key, slot)| (std::cmp::Reverse(slot.height), key.0));
+        for (key, slot) in slots {
+            let image = old_pages[slot.page]
+                .image
+                .crop(slot.x, slot.y, slot.width, slot.height)
+                .unwrap_or_default();
+            let slot = self.place(&image);
+            self.slots.insert(key, slot);
+        }
+    }
+
+    /// Upload pages changed since the last call.
+    pub fn update(&mut self, frame: &mut epi::Frame<'_>) {
+        for texture_id in self.stale.drain(..) {
+            frame.tex_allocator().free(texture_id)
+        }
+        for page in self.pages.iter_mut().filter(|page| page.dirty) {
+            if let Some(texture_id) = page.texture.take() {
+                frame.tex_allocator().free(texture_id)
+            }
+            page.texture = Some(page.image.texture_id(frame));
+            page.dirty = false
+        }
+    }
+
+    /// Free every texture, pages are uploaded again by the next `update`.
+    pub fn free(&mut self, frame: &mut epi::Frame<'_>) {
+        for texture_id in self.stale.drain(..) {
+            frame.tex_allocator().free(texture_id)
+        }
+        for page in &mut self.pages {
+            if let Some(texture_id) = page.texture.take() {
+                frame.tex_allocator().free(texture_id)
+            }
+            page.dirty = true
+        }
+    }
+
+    fn place(&mut self, image: &Image) -> Slot {
+        let (width, height) = (image.width(), image.height());
+        let (padded_width, padded_height) = (width + 2 * self.padding, height + 2 * self.padding);
+        let found = self.pages.iter_mut().enumerate().find_map(|(index, page)| {
+            page.allocate(padded_width, padded_height)
+                .map(|(x, y)| (index, x, y))
+        });
+        let (page, x, y) = match found {
+            Some(found) => found,
+            None => {
+                let mut page = Page::new(
+                    self.page_size.max(padded_width),
+                    self.page_size.max(padded_height),
+                );
+                let (x, y) = page.allocate(padded_width, padded_height).unwrap_or((0, 0));
+                self.pages.push(page);
+                (self.pages.len() - 1, x, y)
+            }
+        };
+        let (x, y) = (x + self.padding, y + self.padding);
+        self.pages[page].blit(x, y, image);
+        Slot {
+            page,
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+}
@@ -0,0 +1,374 @@
+use crate::{unmultiply, Image, ImageViewer, FULL_UV};
+use egui::{
+    pos2, vec2, Align2, Color32, CursorIcon, Pos2, Rect, Response, Sense, Stroke, TextStyle,
+    TextureId, Ui, Vec2,
+};
+
+/// How `ImageCompare` shows the two images.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CompareMode {
+    /// Both overlaid, before on one side of a draggable divider and after on the other.
+    Slider,
+    /// Next to each other, zoom and pan applied to both.
+    SideBySide,
+    /// Absolute per channel difference, identical pixels are black.
+    Difference,
+}
+
+/// Direction the view is split in by `CompareMode::Slider` and `CompareMode::SideBySide`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Split {
+    /// Before on the left, after on the right.
+    Vertical,
+    /// Before on the top, after on the bottom.
+    Horizontal,
+}
+
+/// Before / after comparison of two images with synchronized zoom and pan, drive it by calling `show` every frame.
+///
+/// The after image is stretched to the before image size if they differ.
+/// The textures are owned by this, call `free` before dropping it.
+pub struct ImageCompare {
+    before: Image,
+    after: Image,
+    textures: Option<(TextureId, TextureId)>,
+    difference: Option<TextureId>,
+    mode: CompareMode,
+    split: Split,
+    /// Divider position as a fraction of the view.
+    position: f32,
+    gain: f32,
+    controls: bool,
+    size: Option<Vec2>,
+    view: ImageViewer,
+    dragging_divider: bool,
+}
+
+impl ImageCompare {
+    pub fn new(before: Image, after: Image) -> Self {
+        let view = ImageViewer::new(TextureId::default(), before.size()).controls(false);
+        Self {
+            before,
+            after,
+            textures: None,
+            difference: None,
+            mode: CompareMode::Slider,
+            split: Split::Vertical,
+            position: 0.5,
+            gain: 1.0,
+            controls: true,
+            size: None,
+            view,
+            dragging_divider: false,
+        }
+    }
+
+    /// `CompareMode::Slider` by default.
+    pub fn mode(mut self, mode: CompareMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// `Split::Vertical` by default.
+    pub fn split(mut self, split: Split) -> Self {
+        self.split = split;
+        self
+    }
+
+    /// Multiply the difference to make small changes visible, 1.0 by default.
+    pub fn difference_gain(mut self, gain: f32) -> Self {
+        self.gain = gain.max(0.0);
+        self
+    }
+
+    /// Show mode buttons, fit / 1:1 buttons and zoom level above the images, `true` by default.
+    pub fn controls(mut self, controls: bool) -> Self {
+        self.controls = controls;
+        self
+    }
+
+    /// Size of the view area, all the available space by default.
+    pub fn size(mut self, size: impl Into<Vec2>) -> Self {
+        self.size = Some(size.into());
+        self
+    }
+
+    pub fn set_mode(&mut self, mode: CompareMode) {
+        self.mode = mode
+    }
+
+    pub fn set_split(&mut self, split: Split) {
+        self.split = split
+    }
+
+    /// Divider position as a fraction (0.0..=1.0) of the view.
+    pub fn divider(&self) -> f32 {
+        self.position
+    }
+
+    pub fn set_divider(&mut self, position: f32) {
+        self.position = position.clamp(0.0, 1.0)
+    }
+
+    /// Zoom and pan, shared by both images.
+    pub fn viewer(&mut self) -> &mut ImageViewer {
+        &mut self.view
+    }
+
+    /// Compare other images, keeping zoom and pan if the size didn't change.
+    pub fn set_images(&mut self, before: Image, after: Image, frame: &mut epi::Frame<'_>) {
+        self.free(frame);
+        self.view.set_texture(TextureId::default(), before.size());
+        self.before = before;
+        self.after = after;
+    }
+
+    pub fn before(&self) -> &Image {
+        &self.before
+    }
+
+    pub fn after(&self) -> &Image {
+        &self.after
+    }
+
+    /// Free the textures, they're uploaded again by the next `show`.
+    pub fn free(&mut self, frame: &mut epi::Frame<'_>) {
+        let textures = self.textures.take();
+        for texture_id in textures
+            .into_iter()
+            .flat_map(|(before, after)| vec![before, after])
+            .chain(self.difference.take())
+        {
+            frame.tex_allocator().free(texture_id)
+        }
+    }
+
+    pub fn show(&mut self, ui: &mut Ui, frame: &mut epi::Frame<'_>) -> Response {
+        if self.controls {
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut self.mode, CompareMode::Slider, "Slider");
+                ui.selectable_value(&mut self.mode, CompareMode::SideBySide, "Side by side");
+                ui.selectable_value(&mut self.mode, CompareMode::Difference, "Difference");
+                ui.separator();
+                self.view.controls_ui(ui);
+            });
+        }
+        let (before, after) = match self.textures {
+            Some(textures) => textures,
+            None => {
+                let textures = (self.before.texture_id(frame), self.after.texture_id(frame));
+                self.textures = Some(textures);
+                textures
+            }
+        };
+
+        let size = self.size.unwrap_or_else(|| ui.available_size());
+        let (rect, response) = ui.allocate_exact_size(size, Sense::click_and_drag());
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 0.0, ui.visuals().extreme_bg_color);
+        let (first, second) = self.halves(rect);
+        match self.mode {
+            CompareMode::Slider => {
+                self.view.layout(ui, rect);
+                if response.drag_started() {
+                    self.dragging_divider = self.on_divider(rect, response.hover_pos())
+                }
+                if !response.dragged() {
+                    self.dragging_divider = false
+                }
+                if self.dragging_divider {
+                    if let Some(pos) = response.interact_pointer_pos() {
+                        self.position = match self.split {
+                            Split::Vertical => (pos.x - rect.min.x) / rect.width(),
+                            Split::Horizontal => (pos.y - rect.min.y) / rect.height(),
+                        }
+                        .clamp(0.0, 1.0)
+                    }
+                }
+                self.view
+                    .handle_input(ui, &response, response.hover_pos(), !self.dragging_divider);
+
+                let image_rect = self.view.image_rect();
+                let (first, second) = self.divided(rect);
+                ui.painter_at(first)
+                    .image(before, image_rect, FULL_UV, Color32::WHITE);
+                ui.painter_at(second)
+                    .image(after, image_rect, FULL_UV, Color32::WHITE);
+                let divider = self.divider_rect(rect);
+                let stroke = Stroke::new(2.0, Color32::WHITE);
+                painter.rect_filled(divider, 0.0, stroke.color);
+                painter.circle_filled(divider.center(), 6.0, stroke.color);
+                painter.circle_stroke(divider.center(), 6.0, Stroke::new(1.0, Color32::BLACK));
+                self.paint_labels(ui, first, second);
+            }
+            CompareMode::SideBySide => {
+                // The second half mirrors the first one, zoom anchors are mapped into the first.
+                self.view.layout(ui, first);
+                let hover = response.hover_pos().map(|pos| {
+                    if second.contains(pos) {
+                        pos - (second.min - first.min)
+                    } else {
+                        pos
+                    }
+                });
+                self.view.handle_input(ui, &response, hover, true);
+                let image_rect = self.view.image_rect();
+                ui.painter_at(first)
+                    .image(before, image_rect, FULL_UV, Color32::WHITE);
+                ui.painter_at(second).image(
+                    after,
+                    image_rect.translate(second.min - first.min),
+                    FULL_UV,
+                    Color32::WHITE,
+                );
+                let separator = ui.visuals().widgets.noninteractive.bg_stroke;
+                match self.split {
+                    Split::Vertical => painter.line_segment(
+                        [
+                            pos2(second.min.x, rect.min.y),
+                            pos2(second.min.x, rect.max.y),
+                        ],
+                        separator,
+                    ),
+                    Split::Horizontal => painter.line_segment(
+                        [
+                            pos2(rect.min.x, second.min.y),
+                            pos2(rect.max.x, second.min.y),
+                        ],
+                        separator,
+                    ),
+                }
+                self.paint_labels(ui, first, second);
+            }
+            CompareMode::Difference => {
+                self.view.layout(ui, rect);
+                self.view
+                    .handle_input(ui, &response, response.hover_pos(), true);
+                let difference = match self.difference {
+                    Some(texture_id) => texture_id,
+                    None => {
+                        let texture_id =
+                            difference(&self.before, &self.after, self.gain).texture_id(frame);
+                        self.difference = Some(texture_id);
+                        texture_id
+                    }
+                };
+                painter.image(difference, self.view.image_rect(), FULL_UV, Color32::WHITE);
+            }
+        }
+
+        let cursor = if self.mode == CompareMode::Slider
+            && (self.dragging_divider || self.on_divider(rect, response.hover_pos()))
+        {
+            match self.split {
+                Split::Vertical => CursorIcon::ResizeHorizontal,
+                Split::Horizontal => CursorIcon::ResizeVertical,
+            }
+        } else if response.dragged() {
+            CursorIcon::Grabbing
+        } else {
+            CursorIcon::Grab
+        };
+        response.on_hover_cursor(cursor)
+    }
+
+    fn on_divider(&self, rect: Rect, pos: Option<Pos2>) -> bool {
+        pos.is_some_and(|pos| self.divider_rect(rect).expand(4.0).contains(pos))
+    }
+
+    /// "Before" and "After" in the outer corners of the two parts.
+    fn paint_labels(&self, ui: &Ui, first: Rect, second: Rect) {
+        let color = ui.visuals().text_color();
+        let (after_pos, after_align) = match self.split {
+            Split::Vertical => (second.right_top() + vec2(-4.0, 4.0), Align2::RIGHT_TOP),
+            Split::Horizontal => (second.left_top() + vec2(4.0, 4.0), Align2::LEFT_TOP),
+        };
+        let painter = ui.painter_at(first.union(second));
+        let before_pos = first.left_top() + vec2(4.0, 4.0);
+        painter.text(
+            before_pos,
+            Align2::LEFT_TOP,
+            "Before",
+            TextStyle::Small,
+            color,
+        );
+        painter.text(after_pos, after_align, "After", TextStyle::Small, color);
+    }
+
+    /// Two equal halves of the view, for side by side.
+    fn halves(&self, rect: Rect) -> (Rect, Rect) {
+        match self.split {
+            Split::Vertical => {
+                let x = rect.center().x;
+                (
+                    Rect::from_min_max(rect.min, pos2(x, rect.max.y)),
+                    Rect::from_min_max(pos2(x, rect.min.y), rect.max),
+                )
+            }
+            Split::Horizontal => {
+                let y = rect.center().y;
+                (
+                    Rect::from_min_max(rect.min, pos2(rect.max.x, y)),
+                    Rect::from_min_max(pos2(rect.min.x, y), rect.max),
+                )
+            }
+        }
+    }
+
+    /// The view cut at the divider.
+    fn divided(&self, rect: Rect) -> (Rect, Rect) {
+        match self.split {
+            Split::Vertical => {
+                let x = rect.min.x + rect.width() * self.position;
+                (
+                    Rect::from_min_max(rect.min, pos2(x, rect.max.y)),
+                    Rect::from_min_max(pos2(x, rect.min.y), rect.max),
+                )
+            }
+            Split::Horizontal => {
+                let y = rect.min.y + rect.height() * self.position;
+                (
+                    Rect::from_min_max(rect.min, pos2(rect.max.x, y)),
+                    Rect::from_min_max(pos2(rect.min.x, y), rect.max),
+                )
+            }
+        }
+    }
+
+    fn divider_rect(&self, rect: Rect) -> Rect {
+        let (first, _) = self.divided(rect);
+        match self.split {
+            Split::Vertical => Rect::from_min_max(
+                pos2(first.max.x - 1.0, rect.min.y),
+                pos2(first.max.x + 1.0, rect.max.y),
+            ),
+            Split::Horizontal => Rect::from_min_max(
+                pos2(rect.min.x, first.max.y - 1.0),
+                pos2(rect.max.x, first.max.y + 1.0),
+            ),
+        }
+    }
+}
+
+/// Absolute difference of the straight alpha channels times `gain`, opaque, sized like `before`.
+fn difference(before: &Image, after: &Image, gain: f32) -> Image {
+    let (width, height) = (before.width(), before.height());
+    let (after_width, after_height) = (after.width(), after.height());
+    Image::from_fn(width, height, |x, y| {
+        // Sample `after` as if stretched to the size of `before`.
+        let ax = x * after_width / width.max(1);
+        let ay = y * after_height / height.max(1);
+        let a = before.get_pixel(x, y).map(unmultiply).unwrap_or([0; 4]);
+        let b = after.get_pixel(ax, ay).map(unmultiply).unwrap_or([0; 4]);
+        let channel = |i: usize| {
+            let diff = (a[i] as f32 - b[i] as f32).abs() * gain;
+            diff.min(255.0) as u8
+        };
+        let alpha = channel(3);
+        Color32::from_rgb(
+            channel(0).max(alpha),
+            channel(1).max(alpha),
+            channel(2).max(alpha),
+        )
+    })
+}
@@ -0,0 +1,42 @@
+use crate::Image;
+use image::{DynamicImage, RgbaImage};
+
+impl From<RgbaImage> for Image {
+    fn from(rgba: RgbaImage) -> Self {
+        let (width, height) = (rgba.width() as usize, rgba.height() as usize);
+        // An `ImageBuffer` always holds exactly width * height pixels.
+        Image::from_rgba_unmultiplied(width, height, rgba.as_raw()).unwrap_or_default()
+    }
+}
+
+impl From<DynamicImage> for Image {
+    fn from(image: DynamicImage) -> Self {
+        Image::from(image.into_rgba8())
+    }
+}
+
+impl From<&Image> for RgbaImage {
+    fn from(image: &Image) -> Self {
+        let (width, height) = (image.width() as u32, image.height() as u32);
+        RgbaImage::from_raw(width, height, image.to_rgba_unmultiplied())
+            .unwrap_or_else(|| RgbaImage::new(width, height))
+    }
+}
+
+impl From<Image> for RgbaImage {
+    fn from(image: Image) -> Self {
+        RgbaImage::from(&image)
+    }
+}
+
+impl From<&Image> for DynamicImage {
+    fn from(image: &Image) -> Self {
+        DynamicImage::ImageRgba8(RgbaImage::from(image))
+    }
+}
+
+impl From<Image> for DynamicImage {
+    fn from(image: Image) -> Self {
+        DynamicImage::from(&image)
+    }
+}
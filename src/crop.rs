@@ -0,0 +1,362 @@
+use crate::image_view::fit_image;
+use crate::{Fit, Image, FULL_UV};
+use egui::{
+    pos2, vec2, Align2, Color32, CursorIcon, Pos2, Rect, Response, Sense, Stroke, TextureId, Ui,
+    Vec2,
+};
+
+/// Distance in points from an edge within which it can be grabbed.
+const GRAB: f32 = 6.0;
+
+#[derive(Clone, Copy)]
+enum Drag {
+    Move,
+    /// Edges following the pointer (-1 min, 1 max, 0 neither) while the opposite ones stay at `anchor`.
+    Resize {
+        edges: (i8, i8),
+        anchor: Pos2,
+    },
+}
+
+/// Crop / region selection over an image, drive it by calling `show` every frame.
+///
+/// Drag the edges or corners to resize, inside to move, outside to start a new selection.
+/// The selection is kept in image pixel coordinates.
+///
+/// ```ignore
+/// let mut crop = CropTool::new(texture_id, image.size()).aspect_ratio(1.0).min_size([64.0, 64.0]);
+/// crop.show(ui);
+/// let avatar = crop.crop(&image);
+/// ```
+pub struct CropTool {
+    texture_id: TextureId,
+    image_size: Vec2,
+    selection: Rect,
+    aspect_ratio: Option<f32>,
+    min_size: Vec2,
+    guides: bool,
+    size: Option<Vec2>,
+    drag: Option<Drag>,
+}
+
+impl CropTool {
+    /// `image_size` is the size of the texture in pixels, e.g. `Image::size`. The whole image is selected.
+    pub fn new(texture_id: TextureId, image_size: impl Into<Vec2>) -> Self {
+        let image_size = image_size.into();
+        Self {
+            texture_id,
+            image_size,
+            selection: Rect::from_min_size(Pos2::ZERO, image_size),
+            aspect_ratio: None,
+            min_size: vec2(1.0, 1.0),
+            guides: true,
+            size: None,
+            drag: None,
+        }
+    }
+
+    /// Keep width / height at `ratio`, the selection becomes the largest centered one with that ratio.
+    pub fn aspect_ratio(mut self, ratio: f32) -> Self {
+        self.set_aspect_ratio(Some(ratio));
+        self
+    }
+
+    /// Smallest selection in pixels, 1x1 by default.
+    pub fn min_size(mut self, min_size: impl Into<Vec2>) -> Self {
+        self.min_size = min_size.into();
+        self
+    }
+
+    /// Rule of thirds lines inside the selection, `true` by default.
+    pub fn guides(mut self, guides: bool) -> Self {
+        self.guides = guides;
+        self
+    }
+
+    /// Size of the widget, all the available space by default.
+    pub fn size(mut self, size: impl Into<Vec2>) -> Self {
+        self.size = Some(size.into());
+        self
+    }
+
+    /// Selection in image pixels, fractional while dragging.
+    pub fn selection(&self) -> Rect {
+        self.selection
+    }
+
+    /// Select a region in image pixels, it's cut to the image.
+    pub fn set_selection(&mut self, selection: Rect) {
+        self.selection = selection.intersect(Rect::from_min_size(Pos2::ZERO, self.image_size))
+    }
+
+    /// `None` for a free selection.
+    pub fn set_aspect_ratio(&mut self, ratio: Option<f32>) {
+        self.aspect_ratio = ratio.filter(|ratio| ratio.is_finite() && *ratio > 0.0);
+        if let Some(ratio) = self.aspect_ratio {
+            let size = self.image_size;
+            let size = if size.x / size.y > ratio {
+                vec2(size.y * ratio, size.y)
+            } else {
+                vec2(size.x, size.x / ratio)
+            };
+            self.selection =
+                Rect::from_center_size(pos2(self.image_size.x * 0.5, self.image_size.y * 0.5), size)
+        }
+    }
+
+    /// Show another texture, the whole image is selected again if the size differs.
+    pub fn set_texture(&mut self, texture_id: TextureId, image_size: impl Into<Vec2>) {
+        let image_size = image_size.into();
+        self.texture_id = texture_id;
+        if image_size != self.image_size {
+            self.image_size = image_size;
+            self.selection = Rect::from_min_size(Pos2::ZERO, image_size);
+            self.set_aspect_ratio(self.aspect_ratio)
+        }
+    }
+
+    /// Selection rounded to whole pixels as `(x, y, width, height)`, the arguments of `Image::crop`.
+    pub fn pixel_rect(&self) -> (usize, usize, usize, usize) {
+        let (width, height) = (
+            self.image_size.x.round() as usize,
+            self.image_size.y.round() as usize,
+        );
+        let x = (self.selection.min.x.round().max(0.0) as usize).min(width);
+        let y = (self.selection.min.y.round().max(0.0) as usize).min(height);
+        let max_x = (self.selection.max.x.round().max(0.0) as usize).min(width);
+        let max_y = (self.selection.max.y.round().max(0.0) as usize).min(height);
+        (x, y, max_x.saturating_sub(x), max_y.saturating_sub(y))
+    }
+
+    /// Copy the selection out of `image`, which has to be the content of the texture.
+    pub fn crop(&self, image: &Image) -> Option<Image> {
+        let (x, y, width, height) = self.pixel_rect();
+        image.crop(x, y, width, height)
+    }
+
+    /// Show the image and the selection, the response is marked changed when the selection moves.
+    pub fn show(&mut self, ui: &mut Ui) -> Response {
+        let size = self.size.unwrap_or_else(|| ui.available_size());
+        let (rect, mut response) = ui.allocate_exact_size(size, Sense::drag());
+        let (image_rect, _) = fit_image(Fit::Contain, Align2::CENTER_CENTER, self.image_size, rect);
+        let scale = image_rect.width() / self.image_size.x;
+        let to_screen = |rect: Rect| {
+            Rect::from_min_max(
+                image_rect.min + rect.min.to_vec2() * scale,
+                image_rect.min + rect.max.to_vec2() * scale,
+            )
+        };
+        let image_size = self.image_size;
+        let to_image = |pos: Pos2| {
+            let pos = (pos - image_rect.min) / scale;
+            pos2(
+                pos.x.max(0.0).min(image_size.x),
+                pos.y.max(0.0).min(image_size.y),
+            )
+        };
+
+        let hover_edges = response
+            .hover_pos()
+            .and_then(|pos| edges_at(to_screen(self.selection), pos));
+        if response.drag_started() {
+            let selection = self.selection;
+            self.drag = response
+                .interact_pointer_pos()
+                .map(|pos| match hover_edges {
+                    Some((x, y)) => Drag::Resize {
+                        edges: (x, y),
+                        anchor: pos2(
+                            if x < 0 {
+                                selection.max.x
+                            } else {
+                                selection.min.x
+                            },
+                            if y < 0 {
+                                selection.max.y
+                            } else {
+                                selection.min.y
+                            },
+                        ),
+                    },
+                    None if to_screen(selection).contains(pos) => Drag::Move,
+                    None => Drag::Resize {
+                        edges: (1, 1),
+                        anchor: to_image(pos),
+                    },
+                });
+        }
+        if !response.dragged() {
+            self.drag = None
+        }
+        let previous = self.selection;
+        match self.drag {
+            Some(Drag::Move) => {
+                let delta = response.drag_delta() / scale;
+                let max = self.image_size - self.selection.size();
+                let min = self.selection.min + delta;
+                let min = pos2(min.x.max(0.0).min(max.x), min.y.max(0.0).min(max.y));
+                self.selection = Rect::from_min_size(min, self.selection.size())
+            }
+            Some(Drag::Resize { edges, anchor }) => {
+                if let Some(pos) = response.interact_pointer_pos() {
+                    self.selection = self.resize(edges, anchor, to_image(pos))
+                }
+            }
+            None => {}
+        }
+        if self.selection != previous {
+            response.mark_changed()
+        }
+
+        let painter = ui.painter_at(rect);
+        painter.image(self.texture_id, image_rect, FULL_UV, Color32::WHITE);
+        let selection = to_screen(self.selection);
+        let shade = Color32::from_black_alpha(128);
+        for outside in &[
+            Rect::from_min_max(image_rect.min, pos2(image_rect.max.x, selection.min.y)),
+            Rect::from_min_max(pos2(image_rect.min.x, selection.max.y), image_rect.max),
+            Rect::from_min_max(
+                pos2(image_rect.min.x, selection.min.y),
+                pos2(selection.min.x, selection.max.y),
+            ),
+            Rect::from_min_max(
+                pos2(selection.max.x, selection.min.y),
+                pos2(image_rect.max.x, selection.max.y),
+            ),
+        ] {
+            painter.rect_filled(*outside, 0.0, shade)
+        }
+        if self.guides {
+            let stroke = Stroke::new(1.0, Color32::from_white_alpha(96));
+            for i in 1..3 {
+                let t = i as f32 / 3.0;
+                let x = selection.min.x + selection.width() * t;
+                let y = selection.min.y + selection.height() * t;
+                painter.line_segment([pos2(x, selection.min.y), pos2(x, selection.max.y)], stroke);
+                painter.line_segment([pos2(selection.min.x, y), pos2(selection.max.x, y)], stroke);
+            }
+        }
+        painter.rect_stroke(selection, 0.0, Stroke::new(1.0, Color32::WHITE));
+        for x in &[selection.min.x, selection.center().x, selection.max.x] {
+            for y in &[selection.min.y, selection.center().y, selection.max.y] {
+                let handle = Rect::from_center_size(pos2(*x, *y), vec2(6.0, 6.0));
+                if handle.center() != selection.center() {
+                    painter.rect_filled(handle, 0.0, Color32::WHITE);
+                    painter.rect_stroke(handle, 0.0, Stroke::new(1.0, Color32::BLACK));
+                }
+            }
+        }
+
+        let edges = match self.drag {
+            Some(Drag::Move) => Some((0, 0)),
+            Some(Drag::Resize { edges, .. }) => Some(edges),
+            None => hover_edges,
+        };
+        let cursor = match edges {
+            Some((0, 0)) => CursorIcon::Move,
+            Some((0, _)) => CursorIcon::ResizeVertical,
+            Some((_, 0)) => CursorIcon::ResizeHorizontal,
+            Some((x, y)) if x == y => CursorIcon::ResizeNwSe,
+            Some(_) => CursorIcon::ResizeNeSw,
+            None if response
+                .hover_pos()
+                .is_some_and(|pos| selection.contains(pos)) =>
+            {
+                CursorIcon::Move
+            }
+            None => CursorIcon::Crosshair,
+        };
+        response.on_hover_cursor(cursor)
+    }
+
+    /// Selection with the dragged `edges` at `pointer`, the opposite ones at `anchor`, within
+    /// the image and respecting the min size and aspect ratio.
+    fn resize(&self, edges: (i8, i8), anchor: Pos2, pointer: Pos2) -> Rect {
+        let bounds = self.image_size;
+        let current = self.selection;
+        let mut size = vec2(
+            if edges.0 != 0 {
+                (pointer.x - anchor.x).abs()
+            } else {
+                current.width()
+            },
+            if edges.1 != 0 {
+                (pointer.y - anchor.y).abs()
+            } else {
+                current.height()
+            },
+        )
+        .max(self.min_size);
+        if let Some(ratio) = self.aspect_ratio {
+            size = match edges {
+                (0, _) => vec2(size.y * ratio, size.y),
+                (_, 0) => vec2(size.x, size.x / ratio),
+                _ => {
+                    let width = size.x.max(size.y * ratio);
+                    vec2(width, width / ratio)
+                }
+            };
+        }
+
+        // The selection grows away from the anchor, on the side the pointer is.
+        let forward = (pointer.x >= anchor.x, pointer.y >= anchor.y);
+        let max = vec2(
+            match (edges.0, forward.0) {
+                (0, _) => bounds.x,
+                (_, true) => bounds.x - anchor.x,
+                (_, false) => anchor.x,
+            },
+            match (edges.1, forward.1) {
+                (0, _) => bounds.y,
+                (_, true) => bounds.y - anchor.y,
+                (_, false) => anchor.y,
+            },
+        );
+        if self.aspect_ratio.is_some() {
+            size *= (max.x / size.x).min(max.y / size.y).min(1.0)
+        } else {
+            size = size.min(max)
+        }
+
+        let min = pos2(
+            match (edges.0, forward.0) {
+                (0, _) => (current.center().x - size.x * 0.5)
+                    .max(0.0)
+                    .min(bounds.x - size.x),
+                (_, true) => anchor.x,
+                (_, false) => anchor.x - size.x,
+            },
+            match (edges.1, forward.1) {
+                (0, _) => (current.center().y - size.y * 0.5)
+                    .max(0.0)
+                    .min(bounds.y - size.y),
+                (_, true) => anchor.y,
+                (_, false) => anchor.y - size.y,
+            },
+        );
+        Rect::from_min_size(min, size)
+    }
+}
+
+/// Edges of `selection` (screen rect) under `pos`, -1 min, 1 max, 0 neither.
+fn edges_at(selection: Rect, pos: Pos2) -> Option<(i8, i8)> {
+    if !selection.expand(GRAB).contains(pos) {
+        return None;
+    }
+    let edge = |pos: f32, min: f32, max: f32| {
+        if (pos - min).abs() <= GRAB {
+            -1
+        } else if (pos - max).abs() <= GRAB {
+            1
+        } else {
+            0
+        }
+    };
+    match (
+        edge(pos.x, selection.min.x, selection.max.x),
+        edge(pos.y, selection.min.y, selection.max.y),
+    ) {
+        (0, 0) => None,
+        edges => Some(edges),
+    }
+}
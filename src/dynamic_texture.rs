@@ -0,0 +1,310 @@
+use crate::Image;
+use egui::{Color32, TextureId, Vec2};
+
+/// In place texture updates, for backends that can do them.
+///
+/// `epi::TextureAllocator` can only allocate and free, implement this on top of the
+/// backend (e.g. `glium::Texture2d::write`) to let `DynamicTexture` keep its `TextureId`.
+/// Closures taking the same arguments implement it too.
+pub trait TextureUpdate {
+    /// Overwrite the `width` x `height` region at `x`, `y` of texture `id` with premultiplied `pixels` (row-major),
+    /// return `false` if the texture can't be updated, it's reallocated instead.
+    fn update(
+        &mut self,
+        id: TextureId,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+        pixels: &[Color32],
+    ) -> bool;
+}
+
+impl<F> TextureUpdate for F
+where
+    F: FnMut(TextureId, usize, usize, usize, usize, &[Color32]) -> bool,
+{
+    fn update(
+        &mut self,
+        id: TextureId,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+        pixels: &[Color32],
+    ) -> bool {
+        self(id, x, y, width, height, pixels)
+    }
+}
+
+/// Allocating and freeing textures, all `DynamicTexture` needs from `epi::Frame`.
+trait Textures {
+    fn alloc(&mut self, image: &Image) -> TextureId;
+    fn free(&mut self, texture_id: TextureId);
+}
+
+impl Textures for epi::Frame<'_> {
+    fn alloc(&mut self, image: &Image) -> TextureId {
+        image.texture_id(self)
+    }
+
+    fn free(&mut self, texture_id: TextureId) {
+        self.tex_allocator().free(texture_id)
+    }
+}
+
+/// Texture for images changing often (camera feeds, plots and .etc), drawn from its own copy of the pixels.
+///
+/// Edits only mark a dirty rectangle, the texture is uploaded once per `texture_id` call no matter
+/// how many edits happened in between. With plain `epi` the texture is reallocated on upload
+/// (its id changes), pass a `TextureUpdate` to `texture_id_with` to upload only the dirty rectangle
+/// into the same texture. The texture is owned by this, call `free` before dropping it.
+pub struct DynamicTexture {
+    image: Image,
+    /// Uploaded texture and the size it was uploaded with, a new texture is needed once the size changes.
+    texture: Option<(TextureId, (usize, usize))>,
+    /// `(min_x, min_y, max_x, max_y)`, max exclusive.
+    dirty: Option<(usize, usize, usize, usize)>,
+}
+
+impl DynamicTexture {
+    pub fn new(image: Image) -> Self {
+        Self {
+            image,
+            texture: None,
+            dirty: None,
+        }
+    }
+
+    /// New transparent texture of `width` x `height` pixels.
+    pub fn with_size(width: usize, height: usize) -> Self {
+        Self::new(Image::from_fn(width, height, |_, _| Color32::TRANSPARENT))
+    }
+
+    /// Current pixels.
+    pub fn image(&self) -> &Image {
+        &self.image
+    }
+
+    /// Edit the pixels directly, marks the whole image dirty. Replacing it with an image of another size
+    /// reallocates the texture on the next upload.
+    pub fn image_mut(&mut self) -> &mut Image {
+        self.mark_all_dirty();
+        &mut self.image
+    }
+
+    /// Size in pixels as float, convenient for `ui.image`.
+    pub fn size(&self) -> Vec2 {
+        self.image.size().into()
+    }
+
+    /// Replace the whole image, the size may change.
+    pub fn set_image(&mut self, image: Image) {
+        self.image = image;
+        self.mark_all_dirty()
+    }
+
+    /// Replace every pixel keeping the size, `false` if the number of pixels doesn't match.
+    pub fn set_pixels(&mut self, pixels: &[Color32]) -> bool {
+        if pixels.len() != self.image.pixels().len() {
+            return false;
+        }
+        self.image.pixels_mut().copy_from_slice(pixels);
+        self.mark_all_dirty();
+        true
+    }
+
+    /// Set pixel at `x`, `y`, out of bounds coordinates are ignored.
+    pub fn put_pixel(&mut self, x: usize, y: usize, color: Color32) {
+        if x < self.image.width() && y < self.image.height() {
+            self.image.put_pixel(x, y, color);
+            self.mark_dirty(x, y, 1, 1)
+        }
+    }
+
+    /// Copy `image` in with its top left corner at `x`, `y`, the part out of bounds is cut off.
+    pub fn update_region(&mut self, x: usize, y: usize, image: &Image) {
+        let width = image.width().min(self.image.width().saturating_sub(x));
+        let height = image.height().min(self.image.height().saturating_sub(y));
+        if width == 0 || height == 0 {
+            return;
+        }
+        let stride = self.image.width();
+        for (row, pixels) in image.rows().take(height).enumerate() {
+            let start = (y + row) * stride + x;
+            self.image.pixels_mut()[start..start + width].copy_from_slice(&pixels[..width])
+        }
+        self.mark_dirty(x, y, width, height)
+    }
+
+    /// Mark a region as changed, e.g. after editing through `image_mut` only a small part.
+    pub fn mark_dirty(&mut self, x: usize, y: usize, width: usize, height: usize) {
+        let x1 = (x + width).min(self.image.width());
+        let y1 = (y + height).min(self.image.height());
+        if x >= x1 || y >= y1 {
+            return;
+        }
+        self.dirty = Some(match self.dirty {
+            Some((min_x, min_y, max_x, max_y)) => {
+                (min_x.min(x), min_y.min(y), max_x.max(x1), max_y.max(y1))
+            }
+            None => (x, y, x1, y1),
+        })
+    }
+
+    pub fn mark_all_dirty(&mut self) {
+        self.dirty = Some((0, 0, self.image.width(), self.image.height()))
+    }
+
+    /// Changed region not uploaded yet as `(x, y, width, height)`.
+    pub fn dirty_rect(&self) -> Option<(usize, usize, usize, usize)> {
+        self.dirty
+            .map(|(min_x, min_y, max_x, max_y)| (min_x, min_y, max_x - min_x, max_y - min_y))
+    }
+
+    /// Texture id, reallocating the texture if anything changed since the last call.
+    pub fn texture_id(&mut self, frame: &mut epi::Frame<'_>) -> TextureId {
+        self.upload(frame, None)
+    }
+
+    /// Texture id, uploading only the dirty rectangle through `updater` into the same texture when possible.
+    pub fn texture_id_with(
+        &mut self,
+        frame: &mut epi::Frame<'_>,
+        updater: &mut dyn TextureUpdate,
+    ) -> TextureId {
+        self.upload(frame, Some(updater))
+    }
+
+    /// Free the texture, it's uploaded again by the next `texture_id` call.
+    pub fn free(&mut self, frame: &mut epi::Frame<'_>) {
+        self.free_texture(frame)
+    }
+
+    fn upload<T: Textures + ?Sized>(
+        &mut self,
+        textures: &mut T,
+        updater: Option<&mut dyn TextureUpdate>,
+    ) -> TextureId {
+        let size = (self.image.width(), self.image.height());
+        let texture_id = match self.texture {
+            Some((texture_id, uploaded)) if uploaded == size => texture_id,
+            _ => return self.reallocate(textures),
+        };
+        let (x, y, width, height) = match self.dirty_rect() {
+            Some(rect) => rect,
+            None => return texture_id,
+        };
+        let updater = match updater {
+            Some(updater) => updater,
+            None => return self.reallocate(textures),
+        };
+        let region = match self.image.view(x, y, width, height) {
+            Some(view) => view.to_image(),
+            None => return self.reallocate(textures),
+        };
+        if !updater.update(texture_id, x, y, width, height, region.pixels()) {
+            return self.reallocate(textures);
+        }
+        self.dirty = None;
+        texture_id
+    }
+
+    fn free_texture<T: Textures + ?Sized>(&mut self, textures: &mut T) {
+        if let Some((texture_id, _)) = self.texture.take() {
+            textures.free(texture_id)
+        }
+    }
+
+    fn reallocate<T: Textures + ?Sized>(&mut self, textures: &mut T) -> TextureId {
+        self.free_texture(textures);
+        let texture_id = textures.alloc(&self.image);
+        self.texture = Some((texture_id, (self.image.width(), self.image.height())));
+        self.dirty = None;
+        texture_id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Hands out `TextureId::User` ids and remembers the freed ones.
+    #[derive(Default)]
+    struct MockTextures {
+        allocated: u64,
+        freed: Vec<TextureId>,
+    }
+
+    impl Textures for MockTextures {
+        fn alloc(&mut self, _image: &Image) -> TextureId {
+            self.allocated += 1;
+            TextureId::User(self.allocated)
+        }
+
+        fn free(&mut self, texture_id: TextureId) {
+            self.freed.push(texture_id)
+        }
+    }
+
+    #[test]
+    fn updater_keeps_texture_id() {
+        let mut textures = MockTextures::default();
+        let mut texture = DynamicTexture::with_size(4, 3);
+        let first = texture.upload(&mut textures, None);
+
+        let mut uploads = Vec::new();
+        let mut updater = |id, x, y, width, height, pixels: &[Color32]| {
+            uploads.push((id, x, y, width, height, pixels.to_vec()));
+            true
+        };
+        texture.put_pixel(1, 1, Color32::RED);
+        texture.put_pixel(2, 1, Color32::BLUE);
+        assert_eq!(texture.upload(&mut textures, Some(&mut updater)), first);
+        // Nothing changed, nothing uploaded.
+        assert_eq!(texture.upload(&mut textures, Some(&mut updater)), first);
+
+        assert_eq!(textures.allocated, 1);
+        assert!(textures.freed.is_empty());
+        assert_eq!(
+            uploads,
+            [(first, 1, 1, 2, 1, vec![Color32::RED, Color32::BLUE])]
+        );
+    }
+
+    #[test]
+    fn reallocates_without_updater() {
+        let mut textures = MockTextures::default();
+        let mut texture = DynamicTexture::with_size(4, 3);
+        let first = texture.upload(&mut textures, None);
+        assert_eq!(texture.upload(&mut textures, None), first);
+
+        texture.put_pixel(0, 0, Color32::RED);
+        let second = texture.upload(&mut textures, None);
+        assert_ne!(second, first);
+        assert_eq!(textures.freed, [first]);
+
+        // An updater that can't update falls back to a new texture too.
+        texture.put_pixel(0, 0, Color32::BLUE);
+        let mut failing = |_, _, _, _, _, _: &[Color32]| false;
+        assert_ne!(texture.upload(&mut textures, Some(&mut failing)), second);
+        assert_eq!(textures.freed, [first, second]);
+    }
+
+    #[test]
+    fn size_change_reallocates() {
+        let mut textures = MockTextures::default();
+        let mut texture = DynamicTexture::with_size(4, 3);
+        let first = texture.upload(&mut textures, None);
+
+        let mut updated = false;
+        let mut updater = |_, _, _, _, _, _: &[Color32]| {
+            updated = true;
+            true
+        };
+        *texture.image_mut() = Image::from_fn(6, 5, |_, _| Color32::WHITE);
+        assert_ne!(texture.upload(&mut textures, Some(&mut updater)), first);
+        assert!(!updated);
+        assert_eq!(textures.freed, [first]);
+    }
+}
@@ -0,0 +1,445 @@
+use crate::{
+    async_image::{paint_error, paint_placeholder},
+    image_view::{fit_image, paint_rounded_image},
+    AsyncImage, Fit, ImageLoader, ImageSource, Placeholder,
+};
+use egui::{vec2, Align, Align2, Color32, Id, Key, Rect, Response, Sense, Stroke, Ui};
+use std::{collections::BTreeSet, hash::Hash};
+
+/// How items of a `Gallery` can be selected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SelectionMode {
+    None,
+    Single,
+    /// Ctrl (cmd on mac) click toggles an item, shift click and shift arrows select a range.
+    Multi,
+}
+
+struct Item {
+    source: ImageSource,
+    svg: bool,
+    /// Only alive while the cell is on (or near) the screen.
+    image: Option<AsyncImage>,
+}
+
+/// What happened in a `Gallery` this frame.
+pub struct GalleryResponse {
+    pub response: Response,
+    pub clicked: Option<usize>,
+    pub double_clicked: Option<usize>,
+    pub secondary_clicked: Option<usize>,
+    /// Enter pressed on the keyboard cursor.
+    pub activated: Option<usize>,
+    pub hovered: Option<usize>,
+    pub selection_changed: bool,
+}
+
+#[derive(Default)]
+struct Events {
+    clicked: Option<usize>,
+    double_clicked: Option<usize>,
+    secondary_clicked: Option<usize>,
+    activated: Option<usize>,
+    hovered: Option<usize>,
+    selection_changed: bool,
+}
+
+/// Grid of images from paths or bytes, with as many columns as fit the available width.
+///
+/// Only cells on screen are loaded and uploaded, textures of cells scrolled more than a screen away
+/// are freed and loading them is canceled. Arrow keys, home and end move the keyboard cursor once
+/// the gallery is clicked. The textures are owned by the gallery, call `free` before dropping it.
+pub struct Gallery {
+    items: Vec<Item>,
+    loader: ImageLoader,
+    cell_size: f32,
+    spacing: f32,
+    id_source: Id,
+    mode: SelectionMode,
+    selected: BTreeSet<usize>,
+    /// Keyboard cursor, also the anchor of shift selections.
+    cursor: Option<usize>,
+    anchor: Option<usize>,
+}
+
+impl Default for Gallery {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Gallery {
+    pub fn new() -> Self {
+        Self {
+            items: Vec::new(),
+            loader: ImageLoader::global().clone(),
+            cell_size: 128.0,
+            spacing: 4.0,
+            id_source: Id::new("egui_extras_lib::Gallery"),
+            mode: SelectionMode::Single,
+            selected: BTreeSet::new(),
+            cursor: None,
+            anchor: None,
+        }
+    }
+
+    /// Width and height of a cell, 128.0 by default.
+    pub fn cell_size(mut self, cell_size: f32) -> Self {
+        self.cell_size = cell_size.max(1.0);
+        self
+    }
+
+    /// Gap between cells, 4.0 by default.
+    pub fn spacing(mut self, spacing: f32) -> Self {
+        self.spacing = spacing;
+        self
+    }
+
+    /// `SelectionMode::Single` by default.
+    pub fn selection_mode(mut self, mode: SelectionMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Needed when showing more than one gallery in the same window.
+    pub fn id_source(mut self, id_source: impl Hash) -> Self {
+        self.id_source = Id::new(id_source);
+        self
+    }
+
+    /// Load on this loader instead of `ImageLoader::global()`.
+    pub fn loader(mut self, loader: ImageLoader) -> Self {
+        self.loader = loader;
+        self
+    }
+
+    /// Add an image (.png, .gif, .jpg and .etc), returns its index.
+    pub fn push(&mut self, source: impl Into<ImageSource>) -> usize {
+        self.push_item(source.into(), false)
+    }
+
+    /// Add a svg image, returns its index.
+    pub fn push_svg(&mut self, source: impl Into<ImageSource>) -> usize {
+        self.push_item(source.into(), true)
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn source(&self, index: usize) -> Option<&ImageSource> {
+        self.items.get(index).map(|item| &item.source)
+    }
+
+    /// Remove every item and free their textures.
+    pub fn clear(&mut self, frame: &mut epi::Frame<'_>) {
+        self.free(frame);
+        self.items.clear();
+        self.selected.clear();
+        self.cursor = None;
+        self.anchor = None
+    }
+
+    /// Free every texture, visible cells are loaded again by the next `show`.
+    pub fn free(&mut self, frame: &mut epi::Frame<'_>) {
+        for item in &mut self.items {
+            if let Some(mut image) = item.image.take() {
+                image.free(frame)
+            }
+        }
+    }
+
+    /// Selected indices in ascending order.
+    pub fn selected(&self) -> impl Iterator<Item = usize> + '_ {
+        self.selected.iter().copied()
+    }
+
+    pub fn is_selected(&self, index: usize) -> bool {
+        self.selected.contains(&index)
+    }
+
+    pub fn set_selected(&mut self, index: usize, selected: bool) {
+        if selected && index < self.items.len() {
+            if self.mode == SelectionMode::Single {
+                self.selected.clear()
+            }
+            self.selected.insert(index);
+        } else {
+            self.selected.remove(&index);
+        }
+    }
+
+    pub fn clear_selection(&mut self) {
+        self.selected.clear()
+    }
+
+    /// Index of the keyboard cursor.
+    pub fn cursor(&self) -> Option<usize> {
+        self.cursor
+    }
+
+    /// Show the grid inside a vertical `ScrollArea` using the available width.
+    pub fn show(&mut self, ui: &mut Ui, frame: &mut epi::Frame<'_>) -> GalleryResponse {
+        let id = self.id_source;
+        let step = self.cell_size + self.spacing;
+        let columns = (((ui.available_width() + self.spacing) / step).floor() as usize).max(1);
+        let rows = (self.items.len() + columns - 1) / columns;
+
+        let mut out = Events::default();
+        let has_focus = ui.ctx().memory().has_focus(id);
+        let moved = if has_focus {
+            self.keyboard(ui, columns, &mut out)
+        } else {
+            false
+        };
+
+        let response = egui::ScrollArea::auto_sized().show_viewport(ui, |ui, viewport| {
+            let width = columns as f32 * step - self.spacing;
+            let height = (rows as f32 * step - self.spacing).max(0.0);
+            let (_, content) = ui.allocate_space(vec2(width, height));
+            let response = ui.interact(content, id, Sense::click());
+            if response.clicked() {
+                ui.ctx().memory().request_focus(id)
+            }
+
+            let first_row = ((viewport.min.y / step).floor().max(0.0)) as usize;
+            let last_row = ((viewport.max.y / step).ceil().max(0.0) as usize).min(rows);
+            // Cells within a screen of the visible ones keep their textures.
+            let margin = last_row - first_row.min(last_row) + 1;
+            let keep = first_row.saturating_sub(margin) * columns
+                ..((last_row + margin) * columns).min(self.items.len());
+            for (index, item) in self.items.iter_mut().enumerate() {
+                if !keep.contains(&index) {
+                    if let Some(mut image) = item.image.take() {
+                        image.free(frame)
+                    }
+                }
+            }
+
+            let cell_size = self.cell_size;
+            let cell_rect = |index: usize| {
+                let (row, column) = (index / columns, index % columns);
+                Rect::from_min_size(
+                    content.min + vec2(column as f32 * step, row as f32 * step),
+                    vec2(cell_size, cell_size),
+                )
+            };
+
+            // The cursor may have moved to a cell outside the viewport, bring it into view.
+            if let Some(index) = self.cursor.filter(|_| moved) {
+                let rect = cell_rect(index);
+                let visible = viewport.translate(content.min.to_vec2());
+                let align = if rect.min.y < visible.min.y {
+                    Some(Align::Min)
+                } else if rect.max.y > visible.max.y {
+                    Some(Align::Max)
+                } else {
+                    None
+                };
+                if let Some(align) = align {
+                    ui.interact(rect, id.with("cursor"), Sense::hover())
+                        .scroll_to_me(align)
+                }
+            }
+
+            for index in first_row * columns..(last_row * columns).min(self.items.len()) {
+                let rect = cell_rect(index);
+                let response = ui.interact(rect, id.with(index), Sense::click());
+                self.cell(ui, frame, index, rect, &response);
+
+                if response.hovered() {
+                    out.hovered = Some(index)
+                }
+                if response.clicked() {
+                    ui.ctx().memory().request_focus(id);
+                    let modifiers = ui.input().modifiers;
+                    out.selection_changed |= self.click(index, modifiers.command, modifiers.shift);
+                    out.clicked = Some(index)
+                }
+                if response.double_clicked() {
+                    out.double_clicked = Some(index)
+                }
+                if response.secondary_clicked() {
+                    out.secondary_clicked = Some(index)
+                }
+            }
+            response
+        });
+        GalleryResponse {
+            response,
+            clicked: out.clicked,
+            double_clicked: out.double_clicked,
+            secondary_clicked: out.secondary_clicked,
+            activated: out.activated,
+            hovered: out.hovered,
+            selection_changed: out.selection_changed,
+        }
+    }
+
+    fn push_item(&mut self, source: ImageSource, svg: bool) -> usize {
+        self.items.push(Item {
+            source,
+            svg,
+            image: None,
+        });
+        self.items.len() - 1
+    }
+
+    /// Load (if needed) and paint one cell.
+    fn cell(
+        &mut self,
+        ui: &Ui,
+        frame: &mut epi::Frame<'_>,
+        index: usize,
+        rect: Rect,
+        response: &Response,
+    ) {
+        let item = &mut self.items[index];
+        if item.image.is_none() {
+            let image = if item.svg {
+                AsyncImage::svg(item.source.clone())
+            } else {
+                AsyncImage::new(item.source.clone())
+            };
+            item.image = Some(image.loader(self.loader.clone()))
+        }
+        let image = match item.image.as_mut() {
+            Some(image) => image,
+            None => return,
+        };
+        image.poll(frame);
+
+        let visuals = ui.visuals();
+        let selected = self.selected.contains(&index);
+        if selected {
+            ui.painter()
+                .rect_filled(rect.expand(2.0), 2.0, visuals.selection.bg_fill)
+        } else if response.hovered() {
+            ui.painter()
+                .rect_filled(rect.expand(2.0), 2.0, visuals.widgets.hovered.bg_fill)
+        }
+        let inner = rect.shrink(2.0);
+        if let Some((texture_id, size)) = image.texture() {
+            let (image_rect, uv) = fit_image(Fit::Contain, Align2::CENTER_CENTER, size, inner);
+            paint_rounded_image(
+                ui.painter(),
+                texture_id,
+                image_rect,
+                uv,
+                0.0,
+                Color32::WHITE,
+            )
+        } else if image.error().is_some() {
+            paint_error(ui, inner)
+        } else {
+            paint_placeholder(ui, inner, &Placeholder::Spinner);
+            ui.ctx().request_repaint()
+        }
+        if self.cursor == Some(index) && ui.ctx().memory().has_focus(self.id_source) {
+            ui.painter().rect_stroke(
+                rect.expand(2.0),
+                2.0,
+                Stroke::new(1.0, visuals.text_color()),
+            )
+        }
+    }
+
+    /// Apply a click on `index`, returns whether the selection changed.
+    fn click(&mut self, index: usize, toggle: bool, range: bool) -> bool {
+        let before = self.selected.clone();
+        match self.mode {
+            SelectionMode::None => (),
+            SelectionMode::Single => {
+                self.selected.clear();
+                self.selected.insert(index);
+            }
+            SelectionMode::Multi => {
+                if range {
+                    self.select_range(index)
+                } else if toggle {
+                    if !self.selected.remove(&index) {
+                        self.selected.insert(index);
+                    }
+                } else {
+                    self.selected.clear();
+                    self.selected.insert(index);
+                }
+            }
+        }
+        if !range || self.anchor.is_none() {
+            self.anchor = Some(index)
+        }
+        self.cursor = Some(index);
+        self.selected != before
+    }
+
+    fn select_range(&mut self, index: usize) {
+        let anchor = self.anchor.unwrap_or(index);
+        self.selected.clear();
+        self.selected.extend(anchor.min(index)..=anchor.max(index));
+    }
+
+    /// Keyboard navigation, returns whether the cursor moved.
+    fn keyboard(&mut self, ui: &Ui, columns: usize, out: &mut Events) -> bool {
+        let len = self.items.len();
+        if len == 0 {
+            return false;
+        }
+        let input = ui.input();
+        let current = self.cursor.unwrap_or(0).min(len - 1);
+        let target = if input.key_pressed(Key::ArrowRight) {
+            Some((current + 1).min(len - 1))
+        } else if input.key_pressed(Key::ArrowLeft) {
+            Some(current.saturating_sub(1))
+        } else if input.key_pressed(Key::ArrowDown) {
+            Some(if current + columns < len {
+                current + columns
+            } else {
+                current
+            })
+        } else if input.key_pressed(Key::ArrowUp) {
+            Some(current.checked_sub(columns).unwrap_or(current))
+        } else if input.key_pressed(Key::Home) {
+            Some(0)
+        } else if input.key_pressed(Key::End) {
+            Some(len - 1)
+        } else {
+            None
+        };
+        if input.key_pressed(Key::Enter) && self.cursor.is_some() {
+            out.activated = self.cursor
+        }
+        if input.key_pressed(Key::Space)
+            && self.cursor.is_some()
+            && self.mode != SelectionMode::None
+        {
+            out.selection_changed |= self.click(current, true, false)
+        }
+
+        match target {
+            Some(target) => {
+                let shift = input.modifiers.shift;
+                let before = self.selected.clone();
+                match self.mode {
+                    SelectionMode::None => (),
+                    SelectionMode::Multi if shift => self.select_range(target),
+                    _ => {
+                        self.selected.clear();
+                        self.selected.insert(target);
+                    }
+                }
+                if !shift || self.mode != SelectionMode::Multi {
+                    self.anchor = Some(target)
+                }
+                self.cursor = Some(target);
+                out.selection_changed |= self.selected != before;
+                true
+            }
+            None => false,
+        }
+    }
+}
@@ -0,0 +1,565 @@
+use crate::Image;
+use egui::color::{gamma_u8_from_linear_f32, linear_f32_from_gamma_u8};
+use std::f32::consts::PI;
+
+const BASE83: &[u8] =
+    b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz#$%*+,-.:;=?@[]^_{|}~";
+
+/// Images are shrunk to at most this many pixels per side before hashing, more detail is lost anyway.
+const BLURHASH_MAX_SIZE: usize = 64;
+/// ThumbHash is defined for images up to 100x100.
+const THUMBHASH_MAX_SIZE: usize = 100;
+
+impl Image {
+    /// BlurHash of this image with `components_x` x `components_y` (1..=9) components, 4x3 is a common choice.
+    pub fn blurhash(&self, components_x: u32, components_y: u32) -> Option<String> {
+        if !(1..=9).contains(&components_x) || !(1..=9).contains(&components_y) {
+            return None;
+        }
+        let (width, height, rgba) = shrink_unmultiplied(self, BLURHASH_MAX_SIZE)?;
+        let (nx, ny) = (components_x as usize, components_y as usize);
+
+        let mut factors = Vec::with_capacity(nx * ny);
+        for j in 0..ny {
+            for i in 0..nx {
+                let normalisation = if i == 0 && j == 0 { 1.0 } else { 2.0 };
+                let mut factor = [0.0; 3];
+                for y in 0..height {
+                    let fy = (PI * j as f32 * y as f32 / height as f32).cos();
+                    for x in 0..width {
+                        let basis =
+                            normalisation * (PI * i as f32 * x as f32 / width as f32).cos() * fy;
+                        let p = &rgba[(y * width + x) * 4..];
+                        for c in 0..3 {
+                            factor[c] += basis * linear_f32_from_gamma_u8(p[c])
+                        }
+                    }
+                }
+                let scale = 1.0 / (width * height) as f32;
+                factors.push([factor[0] * scale, factor[1] * scale, factor[2] * scale]);
+            }
+        }
+
+        let mut hash = String::with_capacity(4 + 2 * factors.len());
+        encode83((nx - 1 + (ny - 1) * 9) as u32, 1, &mut hash);
+        let ac = &factors[1..];
+        let maximum = if ac.is_empty() {
+            encode83(0, 1, &mut hash);
+            1.0
+        } else {
+            let actual = ac
+                .iter()
+                .flat_map(|f| f.iter())
+                .fold(0.0f32, |max, v| max.max(v.abs()));
+            let quantised = (actual * 166.0 - 0.5).floor().clamp(0.0, 82.0) as u32;
+            encode83(quantised, 1, &mut hash);
+            (quantised + 1) as f32 / 166.0
+        };
+        let dc = factors[0];
+        let dc = (gamma_u8_from_linear_f32(dc[0]) as u32) << 16
+            | (gamma_u8_from_linear_f32(dc[1]) as u32) << 8
+            | gamma_u8_from_linear_f32(dc[2]) as u32;
+        encode83(dc, 4, &mut hash);
+        for f in ac {
+            let quant = |v: f32| {
+                (sign_pow(v / maximum, 0.5) * 9.0 + 9.5)
+                    .floor()
+                    .clamp(0.0, 18.0) as u32
+            };
+            encode83(
+                quant(f[0]) * 19 * 19 + quant(f[1]) * 19 + quant(f[2]),
+                2,
+                &mut hash,
+            );
+        }
+        Some(hash)
+    }
+
+    /// Decode a BlurHash into a `width` x `height` image, `punch` boosts contrast (1.0 is neutral).
+    pub fn from_blurhash(hash: &str, width: usize, height: usize, punch: f32) -> Option<Image> {
+        let bytes = hash.as_bytes();
+        let size_flag = decode83(bytes.get(0..1)?)?;
+        let (nx, ny) = ((size_flag % 9 + 1) as usize, (size_flag / 9 + 1) as usize);
+        if bytes.len() != 4 + 2 * nx * ny {
+            return None;
+        }
+        let maximum = (decode83(&bytes[1..2])? + 1) as f32 / 166.0 * punch;
+
+        let dc = decode83(&bytes[2..6])?;
+        let mut colors = vec![[
+            linear_f32_from_gamma_u8((dc >> 16) as u8),
+            linear_f32_from_gamma_u8((dc >> 8) as u8),
+            linear_f32_from_gamma_u8(dc as u8),
+        ]];
+        for i in 1..nx * ny {
+            let ac = decode83(&bytes[4 + i * 2..6 + i * 2])?;
+            let channel = |q: u32| sign_pow((q as f32 - 9.0) / 9.0, 2.0) * maximum;
+            colors.push([
+                channel(ac / (19 * 19)),
+                channel(ac / 19 % 19),
+                channel(ac % 19),
+            ]);
+        }
+
+        let mut rgba = Vec::with_capacity(width * height * 4);
+        for y in 0..height {
+            for x in 0..width {
+                let mut pixel = [0.0; 3];
+                for j in 0..ny {
+                    let fy = (PI * y as f32 * j as f32 / height as f32).cos();
+                    for i in 0..nx {
+                        let basis = (PI * x as f32 * i as f32 / width as f32).cos() * fy;
+                        let color = colors[i + j * nx];
+                        for c in 0..3 {
+                            pixel[c] += color[c] * basis
+                        }
+                    }
+                }
+                rgba.extend_from_slice(&[
+                    gamma_u8_from_linear_f32(pixel[0]),
+                    gamma_u8_from_linear_f32(pixel[1]),
+                    gamma_u8_from_linear_f32(pixel[2]),
+                    255,
+                ]);
+            }
+        }
+        Image::from_rgba_unmultiplied(width, height, &rgba)
+    }
+
+    /// ThumbHash of this image (supports transparency and keeps the aspect ratio).
+    pub fn thumbhash(&self) -> Option<Vec<u8>> {
+        let (w, h, rgba) = shrink_unmultiplied(self, THUMBHASH_MAX_SIZE)?;
+
+        // Average color, used as background for transparent pixels.
+        let (mut avg_r, mut avg_g, mut avg_b, mut avg_a) = (0.0, 0.0, 0.0, 0.0);
+        for p in rgba.chunks_exact(4) {
+            let alpha = p[3] as f32 / 255.0;
+            avg_r += alpha / 255.0 * p[0] as f32;
+            avg_g += alpha / 255.0 * p[1] as f32;
+            avg_b += alpha / 255.0 * p[2] as f32;
+            avg_a += alpha;
+        }
+        if avg_a > 0.0 {
+            avg_r /= avg_a;
+            avg_g /= avg_a;
+            avg_b /= avg_a;
+        }
+
+        let has_alpha = avg_a < (w * h) as f32;
+        let l_limit = if has_alpha { 5.0 } else { 7.0 };
+        let max_side = w.max(h) as f32;
+        let lx = ((l_limit * w as f32 / max_side).round() as usize).max(1);
+        let ly = ((l_limit * h as f32 / max_side).round() as usize).max(1);
+
+        // RGBA to LPQA (luminance, yellow - blue, red - green, alpha) composited atop the average color.
+        let n = w * h;
+        let (mut l, mut p, mut q, mut a) = (
+            Vec::with_capacity(n),
+            Vec::with_capacity(n),
+            Vec::with_capacity(n),
+            Vec::with_capacity(n),
+        );
+        for px in rgba.chunks_exact(4) {
+            let alpha = px[3] as f32 / 255.0;
+            let r = avg_r * (1.0 - alpha) + alpha / 255.0 * px[0] as f32;
+            let g = avg_g * (1.0 - alpha) + alpha / 255.0 * px[1] as f32;
+            let b = avg_b * (1.0 - alpha) + alpha / 255.0 * px[2] as f32;
+            l.push((r + g + b) / 3.0);
+            p.push((r + g) / 2.0 - b);
+            q.push(r - g);
+            a.push(alpha);
+        }
+
+        let encode_channel = |channel: &[f32], nx: usize, ny: usize| {
+            let (mut dc, mut ac, mut scale) = (0.0, Vec::new(), 0.0f32);
+            let mut fx = vec![0.0; w];
+            for cy in 0..ny {
+                let mut cx = 0;
+                while cx * ny < nx * (ny - cy) {
+                    for (x, f) in fx.iter_mut().enumerate() {
+                        *f = (PI / w as f32 * cx as f32 * (x as f32 + 0.5)).cos();
+                    }
+                    let mut f = 0.0;
+                    for y in 0..h {
+                        let fy = (PI / h as f32 * cy as f32 * (y as f32 + 0.5)).cos();
+                        for x in 0..w {
+                            f += channel[x + y * w] * fx[x] * fy;
+                        }
+                    }
+                    f /= n as f32;
+                    if cx > 0 || cy > 0 {
+                        ac.push(f);
+                        scale = scale.max(f.abs());
+                    } else {
+                        dc = f;
+                    }
+                    cx += 1;
+                }
+            }
+            if scale > 0.0 {
+                for f in &mut ac {
+                    *f = 0.5 + 0.5 / scale * *f;
+                }
+            }
+            (dc, ac, scale)
+        };
+        let (l_dc, l_ac, l_scale) = encode_channel(&l, lx.max(3), ly.max(3));
+        let (p_dc, p_ac, p_scale) = encode_channel(&p, 3, 3);
+        let (q_dc, q_ac, q_scale) = encode_channel(&q, 3, 3);
+        let alpha = if has_alpha {
+            Some(encode_channel(&a, 5, 5))
+        } else {
+            None
+        };
+
+        let is_landscape = w > h;
+        let header24 = (63.0 * l_dc).round() as u32
+            | ((31.5 + 31.5 * p_dc).round() as u32) << 6
+            | ((31.5 + 31.5 * q_dc).round() as u32) << 12
+            | ((31.0 * l_scale).round() as u32) << 18
+            | (has_alpha as u32) << 23;
+        let header16 = (if is_landscape { ly } else { lx }) as u32
+            | ((63.0 * p_scale).round() as u32) << 3
+            | ((63.0 * q_scale).round() as u32) << 9
+            | (is_landscape as u32) << 15;
+        let mut hash = vec![
+            header24 as u8,
+            (header24 >> 8) as u8,
+            (header24 >> 16) as u8,
+            header16 as u8,
+            (header16 >> 8) as u8,
+        ];
+        if let Some((a_dc, _, a_scale)) = &alpha {
+            hash.push((15.0 * a_dc).round() as u8 | ((15.0 * a_scale).round() as u8) << 4);
+        }
+
+        let ac_start = hash.len();
+        let mut acs = vec![l_ac, p_ac, q_ac];
+        if let Some((_, a_ac, _)) = alpha {
+            acs.push(a_ac);
+        }
+        for (i, f) in acs.iter().flatten().enumerate() {
+            let index = ac_start + (i >> 1);
+            if index >= hash.len() {
+                hash.push(0);
+            }
+            hash[index] |= ((15.0 * f).round() as u8) << ((i & 1) << 2);
+        }
+        Some(hash)
+    }
+
+    /// Decode a ThumbHash into a small image (32 pixels on the longest side).
+    pub fn from_thumbhash(hash: &[u8]) -> Option<Image> {
+        if hash.len() < 5 {
+            return None;
+        }
+        let header24 = hash[0] as u32 | (hash[1] as u32) << 8 | (hash[2] as u32) << 16;
+        let header16 = hash[3] as u32 | (hash[4] as u32) << 8;
+        let l_dc = (header24 & 63) as f32 / 63.0;
+        let p_dc = ((header24 >> 6) & 63) as f32 / 31.5 - 1.0;
+        let q_dc = ((header24 >> 12) & 63) as f32 / 31.5 - 1.0;
+        let l_scale = ((header24 >> 18) & 31) as f32 / 31.0;
+        let has_alpha = (header24 >> 23) != 0;
+        let p_scale = ((header16 >> 3) & 63) as f32 / 63.0;
+        let q_scale = ((header16 >> 9) & 63) as f32 / 63.0;
+        let is_landscape = (header16 >> 15) != 0;
+        let l_max = if has_alpha { 5 } else { 7 };
+        let l_short = (header16 & 7) as usize;
+        let (lx, ly) = if is_landscape {
+            (l_max, l_short)
+        } else {
+            (l_short, l_max)
+        };
+        let (lx, ly) = (lx.max(3), ly.max(3));
+        if has_alpha && hash.len() < 6 {
+            return None;
+        }
+        let a_dc = if has_alpha {
+            (hash[5] & 15) as f32 / 15.0
+        } else {
+            1.0
+        };
+        let a_scale = if has_alpha {
+            (hash[5] >> 4) as f32 / 15.0
+        } else {
+            0.0
+        };
+
+        // Boost saturation by 1.25x to compensate for quantization.
+        let ac_start = if has_alpha { 6 } else { 5 };
+        let mut ac_index = 0;
+        let mut decode_channel = |nx: usize, ny: usize, scale: f32| -> Option<Vec<f32>> {
+            let mut ac = Vec::new();
+            for cy in 0..ny {
+                let mut cx = if cy > 0 { 0 } else { 1 };
+                while cx * ny < nx * (ny - cy) {
+                    let byte = *hash.get(ac_start + (ac_index >> 1))?;
+                    let v = (byte >> ((ac_index & 1) << 2)) & 15;
+                    ac.push((v as f32 / 7.5 - 1.0) * scale);
+                    ac_index += 1;
+                    cx += 1;
+                }
+            }
+            Some(ac)
+        };
+        let l_ac = decode_channel(lx, ly, l_scale)?;
+        let p_ac = decode_channel(3, 3, p_scale * 1.25)?;
+        let q_ac = decode_channel(3, 3, q_scale * 1.25)?;
+        let a_ac = if has_alpha {
+            decode_channel(5, 5, a_scale)?
+        } else {
+            Vec::new()
+        };
+
+        let ratio = thumbhash_aspect_ratio(hash);
+        let (w, h) = if ratio > 1.0 {
+            (32, (32.0 / ratio).round() as usize)
+        } else {
+            ((32.0 * ratio).round() as usize, 32)
+        };
+        let (w, h) = (w.max(1), h.max(1));
+        let n_x = lx.max(if has_alpha { 5 } else { 3 });
+        let n_y = ly.max(if has_alpha { 5 } else { 3 });
+        let (mut fx, mut fy) = (vec![0.0; n_x], vec![0.0; n_y]);
+        let mut rgba = Vec::with_capacity(w * h * 4);
+        for y in 0..h {
+            for x in 0..w {
+                let (mut l, mut p, mut q, mut a) = (l_dc, p_dc, q_dc, a_dc);
+                for (cx, f) in fx.iter_mut().enumerate() {
+                    *f = (PI / w as f32 * (x as f32 + 0.5) * cx as f32).cos();
+                }
+                for (cy, f) in fy.iter_mut().enumerate() {
+                    *f = (PI / h as f32 * (y as f32 + 0.5) * cy as f32).cos();
+                }
+
+                let mut j = 0;
+                for (cy, f) in fy[..ly].iter().enumerate() {
+                    let fy2 = f * 2.0;
+                    let mut cx = if cy > 0 { 0 } else { 1 };
+                    while cx * ly < lx * (ly - cy) {
+                        l += l_ac[j] * fx[cx] * fy2;
+                        j += 1;
+                        cx += 1;
+                    }
+                }
+                let mut j = 0;
+                for (cy, f) in fy[..3].iter().enumerate() {
+                    let fy2 = f * 2.0;
+                    for f in &fx[if cy > 0 { 0 } else { 1 }..3 - cy] {
+                        let f = f * fy2;
+                        p += p_ac[j] * f;
+                        q += q_ac[j] * f;
+                        j += 1;
+                    }
+                }
+                if has_alpha {
+                    let mut j = 0;
+                    for (cy, f) in fy[..5].iter().enumerate() {
+                        let fy2 = f * 2.0;
+                        for f in &fx[if cy > 0 { 0 } else { 1 }..5 - cy] {
+                            a += a_ac[j] * f * fy2;
+                            j += 1;
+                        }
+                    }
+                }
+
+                let b = l - 2.0 / 3.0 * p;
+                let r = (3.0 * l - b + q) / 2.0;
+                let g = r - q;
+                let to_u8 = |v: f32| (255.0 * v.min(1.0)).max(0.0) as u8;
+                rgba.extend_from_slice(&[to_u8(r), to_u8(g), to_u8(b), to_u8(a)]);
+            }
+        }
+        Image::from_rgba_unmultiplied(w, h, &rgba)
+    }
+}
+
+/// Approximate width / height of the image a ThumbHash was made from.
+pub fn thumbhash_aspect_ratio(hash: &[u8]) -> f32 {
+    if hash.len() < 5 {
+        return 1.0;
+    }
+    let header = hash[3];
+    let has_alpha = hash[2] & 0x80 != 0;
+    let is_landscape = hash[4] & 0x80 != 0;
+    let l_max = if has_alpha { 5 } else { 7 };
+    let (lx, ly) = if is_landscape {
+        (l_max, header & 7)
+    } else {
+        (header & 7, l_max)
+    };
+    lx.max(1) as f32 / ly.max(1) as f32
+}
+
+fn encode83(value: u32, length: u32, out: &mut String) {
+    for i in 1..=length {
+        let digit = (value / 83u32.pow(length - i)) % 83;
+        out.push(BASE83[digit as usize] as char);
+    }
+}
+
+fn decode83(digits: &[u8]) -> Option<u32> {
+    digits.iter().try_fold(0u32, |value, c| {
+        let digit = BASE83.iter().position(|b| b == c)? as u32;
+        Some(value * 83 + digit)
+    })
+}
+
+fn sign_pow(value: f32, exp: f32) -> f32 {
+    value.abs().powf(exp).copysign(value)
+}
+
+/// Box-filter `image` down to at most `max_size` pixels per side, as straight alpha RGBA bytes.
+fn shrink_unmultiplied(image: &Image, max_size: usize) -> Option<(usize, usize, Vec<u8>)> {
+    let (width, height) = (image.width(), image.height());
+    if width == 0 || height == 0 {
+        return None;
+    }
+    let scale = (max_size as f32 / width.max(height) as f32).min(1.0);
+    let w = ((width as f32 * scale).round() as usize).max(1);
+    let h = ((height as f32 * scale).round() as usize).max(1);
+    if (w, h) == (width, height) {
+        return Some((w, h, image.to_rgba_unmultiplied()));
+    }
+    // Average premultiplied values so transparent pixels don't bleed their color.
+    let small = Image::from_fn(w, h, |x, y| {
+        let (x0, x1) = (x * width / w, ((x + 1) * width / w).max(x * width / w + 1));
+        let (y0, y1) = (
+            y * height / h,
+            ((y + 1) * height / h).max(y * height / h + 1),
+        );
+        let mut sum = [0u32; 4];
+        for row in image.rows().skip(y0).take(y1 - y0) {
+            for pixel in &row[x0..x1] {
+                for (s, c) in sum.iter_mut().zip(pixel.to_array().iter()) {
+                    *s += *c as u32
+                }
+            }
+        }
+        let count = ((x1 - x0) * (y1 - y0)) as u32;
+        egui::Color32::from_rgba_premultiplied(
+            (sum[0] / count) as u8,
+            (sum[1] / count) as u8,
+            (sum[2] / count) as u8,
+            (sum[3] / count) as u8,
+        )
+    });
+    Some((w, h, small.to_rgba_unmultiplied()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use egui::Color32;
+
+    /// Opaque pattern without flat areas, flat areas give AC components right on a rounding edge.
+    fn pattern(x: usize, y: usize) -> Color32 {
+        Color32::from_rgb(
+            ((x * 71 + y * 29 + 13) % 256) as u8,
+            ((x * 17 + y * 83 + 101) % 256) as u8,
+            ((x * x * 5 + y * 47 + 7) % 256) as u8,
+        )
+    }
+
+    fn rgba(image: &Image, x: usize, y: usize) -> [u8; 4] {
+        image.get_pixel(x, y).unwrap().to_array()
+    }
+
+    // Vectors from the reference TypeScript implementation (woltapp/blurhash).
+    #[test]
+    fn blurhash_matches_reference() {
+        let image = Image::from_fn(8, 6, pattern);
+        let hash = "LTHLb*%LMnwR_DJ^NCFdt0Jxn;FO";
+        assert_eq!(image.blurhash(4, 3).as_deref(), Some(hash));
+        assert_eq!(image.blurhash(1, 1).as_deref(), Some("00HLb*"));
+        assert_eq!(
+            image.blurhash(2, 5).as_deref(),
+            Some("bdHLb*%L?mO5s%O6.kXhpAQ:")
+        );
+
+        let decoded = Image::from_blurhash(hash, 3, 2, 1.0).unwrap();
+        let expected = [
+            [130, 179, 133, 255],
+            [203, 201, 0, 255],
+            [170, 188, 196, 255],
+            [182, 134, 179, 255],
+            [136, 159, 178, 255],
+            [163, 143, 149, 255],
+        ];
+        for (i, expected) in expected.iter().enumerate() {
+            assert_eq!(rgba(&decoded, i % 3, i / 3), *expected);
+        }
+    }
+
+    #[test]
+    fn blurhash_round_trip() {
+        let image = Image::from_fn(40, 30, |x, _| {
+            if x < 20 {
+                Color32::from_rgb(200, 30, 30)
+            } else {
+                Color32::from_rgb(30, 30, 200)
+            }
+        });
+        let hash = image.blurhash(4, 3).unwrap();
+        assert_eq!(hash.len(), 4 + 2 * 4 * 3);
+        let decoded = Image::from_blurhash(&hash, 40, 30, 1.0).unwrap();
+        let (left, right) = (rgba(&decoded, 2, 15), rgba(&decoded, 37, 15));
+        assert!(left[0] > left[2] && right[2] > right[0]);
+
+        assert!(image.blurhash(0, 3).is_none());
+        assert!(Image::from_blurhash(&hash[..hash.len() - 1], 4, 4, 1.0).is_none());
+    }
+
+    // Vectors from the reference JavaScript implementation (evanw/thumbhash).
+    #[test]
+    fn thumbhash_matches_reference() {
+        let hash = Image::from_fn(8, 6, pattern).thumbhash().unwrap();
+        assert_eq!(
+            hash,
+            [
+                223, 7, 6, 45, 132, 85, 116, 85, 24, 99, 197, 70, 109, 234, 135, 4, 57, 191, 153,
+                11, 128
+            ]
+        );
+        let decoded = Image::from_thumbhash(&hash).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (32, 23));
+        assert_eq!(rgba(&decoded, 0, 0), [76, 97, 27, 255]);
+        assert_eq!(rgba(&decoded, 5, 0), [105, 129, 46, 255]);
+        assert_eq!(rgba(&decoded, 17, 0), [148, 169, 78, 255]);
+
+        let transparent = Image::from_fn(6, 8, |x, y| {
+            let alpha = if x < 3 {
+                255
+            } else {
+                ((x * 53 + y * 97 + 11) % 256) as u8
+            };
+            let [r, g, b, _] = pattern(x, y).to_array();
+            Color32::from_rgba_unmultiplied(r, g, b, alpha)
+        });
+        let hash = transparent.thumbhash().unwrap();
+        assert_eq!(
+            hash,
+            [
+                93, 248, 133, 44, 10, 43, 101, 87, 53, 51, 113, 178, 143, 148, 249, 153, 72, 240,
+                72, 120, 120, 136, 119, 120, 7
+            ]
+        );
+        let decoded = Image::from_thumbhash(&hash).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (26, 32));
+        assert_eq!(rgba(&decoded, 0, 0)[3], 223);
+        assert_eq!(rgba(&decoded, 25, 31)[3], 168);
+    }
+
+    #[test]
+    fn thumbhash_round_trip() {
+        let image = Image::from_fn(50, 100, |_, y| Color32::from_gray((y * 2) as u8));
+        let hash = image.thumbhash().unwrap();
+        assert!((thumbhash_aspect_ratio(&hash) - 0.5).abs() < 0.2);
+        let decoded = Image::from_thumbhash(&hash).unwrap();
+        assert!(decoded.height() > decoded.width());
+        let (top, bottom) = (rgba(&decoded, 8, 1), rgba(&decoded, 8, 30));
+        assert!(top[0] < bottom[0]);
+
+        assert!(Image::from_thumbhash(&hash[..4]).is_none());
+    }
+}
@@ -0,0 +1,125 @@
+use crate::Image;
+use egui::{Color32, Rgba};
+
+/// Tone mapping operator used to turn linear HDR values into displayable pixels,
+/// the value of each variant is the exposure in stops (0.0 leaves the values untouched).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ToneMapping {
+    /// Scale by exposure then clip anything above 1.0.
+    Exposure(f32),
+    /// Reinhard operator `x / (1 + x)`.
+    Reinhard(f32),
+    /// ACES filmic curve (Narkowicz fit).
+    Aces(f32),
+}
+
+impl Default for ToneMapping {
+    fn default() -> Self {
+        ToneMapping::Reinhard(0.0)
+    }
+}
+
+impl ToneMapping {
+    /// Map a single linear channel value into 0.0..=1.0.
+    pub fn map(&self, value: f32) -> f32 {
+        let (exposure, curve): (f32, fn(f32) -> f32) = match *self {
+            ToneMapping::Exposure(stops) => (stops, |x| x),
+            ToneMapping::Reinhard(stops) => (stops, |x| x / (1.0 + x)),
+            ToneMapping::Aces(stops) => (stops, |x| {
+                (x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14)
+            }),
+        };
+        let x = value.max(0.0) * exposure.exp2();
+        curve(x).clamp(0.0, 1.0)
+    }
+}
+
+/// High bit-depth or floating point image, pixels are linear unmultiplied RGBA `f32` (row-major).
+///
+/// Decoding files with `HdrImage::new` needs the `image` feature.
+#[derive(Clone, Default)]
+pub struct HdrImage {
+    pub width: usize,
+    pub height: usize,
+    pub data: Vec<f32>,
+}
+
+impl HdrImage {
+    /// New hdr image from linear unmultiplied RGBA `f32` values, `None` if data length doesn't match the size.
+    pub fn from_rgba_f32(width: usize, height: usize, data: Vec<f32>) -> Option<HdrImage> {
+        if data.len() != width * height * 4 {
+            return None;
+        }
+        Some(HdrImage {
+            width,
+            height,
+            data,
+        })
+    }
+
+    /// New hdr image from bytes of 16-bit .png/.tiff, .hdr, .exr and any other format supported by image crate.
+    ///
+    /// Integer formats are treated as sRGB encoded and converted to linear, float formats are taken as is.
+    /// Needs the `image` feature.
+    #[cfg(feature = "image")]
+    pub fn new(bytes: &[u8]) -> Option<HdrImage> {
+        let image = image::load_from_memory(bytes).ok()?;
+        let is_float = matches!(
+            image,
+            image::DynamicImage::ImageRgb32F(_) | image::DynamicImage::ImageRgba32F(_)
+        );
+        let rgba = image.into_rgba32f();
+        let (width, height) = (rgba.width() as usize, rgba.height() as usize);
+        let mut data = rgba.into_raw();
+        if !is_float {
+            for pixel in data.chunks_exact_mut(4) {
+                for channel in &mut pixel[..3] {
+                    *channel = egui::color::linear_from_gamma(*channel)
+                }
+            }
+        }
+        HdrImage::from_rgba_f32(width, height, data)
+    }
+
+    /// Linear unmultiplied RGBA of pixel at `x`, `y`.
+    pub fn pixel(&self, x: usize, y: usize) -> Option<[f32; 4]> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let i = (y * self.width + x) * 4;
+        Some([
+            self.data[i],
+            self.data[i + 1],
+            self.data[i + 2],
+            self.data[i + 3],
+        ])
+    }
+
+    /// Smallest and largest channel value (alpha excluded), handy to pick an exposure.
+    pub fn range(&self) -> (f32, f32) {
+        self.data
+            .chunks_exact(4)
+            .flat_map(|pixel| pixel[..3].iter().copied())
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), v| {
+                (min.min(v), max.max(v))
+            })
+    }
+
+    /// Tone map into a displayable (premultiplied) image, ready for `Image::texture_id`.
+    pub fn to_image(&self, tone_mapping: ToneMapping) -> Image {
+        let pixels = self
+            .data
+            .chunks_exact(4)
+            .map(|p| {
+                let a = p[3].clamp(0.0, 1.0);
+                Color32::from(Rgba::from_rgba_premultiplied(
+                    tone_mapping.map(p[0]) * a,
+                    tone_mapping.map(p[1]) * a,
+                    tone_mapping.map(p[2]) * a,
+                    a,
+                ))
+            })
+            .collect();
+        Image::from_parts(self.width, self.height, pixels)
+    }
+}
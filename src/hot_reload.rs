@@ -0,0 +1,191 @@
+use crate::{
+    loader::{IMAGE, SVG},
+    ImageLoader, ImageTask, Priority,
+};
+use asynchron::Progress;
+use egui::{TextureId, Vec2};
+use notify::{DebouncedEvent, RecommendedWatcher, RecursiveMode, Watcher};
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::mpsc::{channel, Receiver},
+    time::Duration,
+};
+
+struct Entry {
+    svg: bool,
+    texture: Option<(TextureId, Vec2)>,
+    task: Option<ImageTask>,
+    /// Not loaded yet (or freed), load without waiting for a change.
+    unloaded: bool,
+}
+
+/// Loads images and svgs from files and reloads them whenever the file changes on disk.
+///
+/// Meant for development, e.g. tweaking icons while the app is running.
+/// Parent directories are watched (not the files) so editors saving through a rename are noticed too.
+/// Call `update` every frame, it swaps textures of changed files once they're decoded,
+/// the old texture is kept if the new content fails to load (e.g. a half written file).
+///
+/// Only files registered with `HotReload::load_image` or `HotReload::load_svg` are watched here,
+/// call `ImageLoader::watch` to reload images loaded by `Image::load_image`, `Image::load_svg`
+/// or an `ImageLoader` directly.
+pub struct HotReload {
+    watcher: RecommendedWatcher,
+    events: Receiver<DebouncedEvent>,
+    loader: ImageLoader,
+    dirs: HashSet<PathBuf>,
+    entries: HashMap<PathBuf, Entry>,
+}
+
+impl HotReload {
+    /// New watcher, events for the same file within `debounce` are merged into one reload.
+    ///
+    /// `None` if the platform watcher (inotify on linux) can't be created.
+    pub fn new(debounce: Duration) -> Option<Self> {
+        let (tx, events) = channel();
+        let watcher = notify::watcher(tx, debounce).ok()?;
+        Some(Self {
+            watcher,
+            events,
+            loader: ImageLoader::global().clone(),
+            dirs: HashSet::new(),
+            entries: HashMap::new(),
+        })
+    }
+
+    /// Load on this loader instead of `ImageLoader::global()`.
+    pub fn loader(mut self, loader: ImageLoader) -> Self {
+        self.loader = loader;
+        self
+    }
+
+    /// Load an image (.png, .gif, .jpg and .etc) and keep it up to date, `false` if the file can't be watched.
+    pub fn load_image(&mut self, path: impl AsRef<Path>) -> bool {
+        self.watch(path.as_ref(), false)
+    }
+
+    /// Load a svg image and keep it up to date, `false` if the file can't be watched.
+    pub fn load_svg(&mut self, path: impl AsRef<Path>) -> bool {
+        self.watch(path.as_ref(), true)
+    }
+
+    /// Texture id and image size of a watched file, `None` until first loaded.
+    pub fn texture(&self, path: impl AsRef<Path>) -> Option<(TextureId, Vec2)> {
+        self.entries
+            .get(&absolute(path.as_ref()))
+            .and_then(|entry| entry.texture)
+    }
+
+    /// Stop watching a file and free its texture.
+    pub fn unwatch(&mut self, path: impl AsRef<Path>, frame: &mut epi::Frame<'_>) {
+        let path = absolute(path.as_ref());
+        if let Some(entry) = self.entries.remove(&path) {
+            if let Some((texture_id, _)) = entry.texture {
+                frame.tex_allocator().free(texture_id)
+            }
+        }
+        if let Some(dir) = path.parent() {
+            if !self.entries.keys().any(|path| path.parent() == Some(dir)) {
+                let _ = self.watcher.unwatch(dir);
+                self.dirs.remove(dir);
+            }
+        }
+    }
+
+    /// Free every texture, files stay watched and are loaded again by the next `update`.
+    pub fn free(&mut self, frame: &mut epi::Frame<'_>) {
+        for entry in self.entries.values_mut() {
+            if let Some((texture_id, _)) = entry.texture.take() {
+                frame.tex_allocator().free(texture_id)
+            }
+            entry.task = None;
+            entry.unloaded = true
+        }
+    }
+
+    /// Handle file changes and swap finished textures, returns the files whose texture changed.
+    pub fn update(&mut self, frame: &mut epi::Frame<'_>) -> Vec<PathBuf> {
+        let mut changed = HashSet::new();
+        while let Ok(event) = self.events.try_recv() {
+            match event {
+                DebouncedEvent::Create(path)
+                | DebouncedEvent::Write(path)
+                | DebouncedEvent::Rename(_, path) => {
+                    changed.insert(path);
+                }
+                // Watch may be lost (e.g. queue overflow), reload everything.
+                DebouncedEvent::Rescan => changed.extend(self.entries.keys().cloned()),
+                _ => (),
+            }
+        }
+
+        let mut swapped = Vec::new();
+        for (path, entry) in self.entries.iter_mut() {
+            if entry.unloaded || changed.contains(path) {
+                entry.unloaded = false;
+                let source = path.to_string_lossy().into_owned();
+                if let Some(task) = entry.task.take() {
+                    task.cancel()
+                }
+                // A load still in flight may have read the old content, start a new job instead of joining it.
+                let id = if entry.svg { SVG } else { IMAGE };
+                entry.task = Some(self.loader.reload(id, source.into(), Priority::High))
+            }
+
+            let mut image = None;
+            if let Some(task) = &entry.task {
+                task.try_resolve(|progress, _| {
+                    if let Progress::Completed(loaded) = progress {
+                        image = Some(loaded)
+                    }
+                });
+                if task.is_done() {
+                    entry.task = None
+                }
+            }
+            if let Some(image) = image {
+                if let Some((texture_id, _)) = entry.texture {
+                    frame.tex_allocator().free(texture_id)
+                }
+                entry.texture = Some((image.texture_id(frame), image.size().into()));
+                swapped.push(path.clone())
+            }
+        }
+        swapped
+    }
+
+    fn watch(&mut self, path: &Path, svg: bool) -> bool {
+        let path = absolute(path);
+        let dir = match path.parent() {
+            Some(dir) => dir.to_path_buf(),
+            None => return false,
+        };
+        if !self.dirs.contains(&dir) {
+            if self
+                .watcher
+                .watch(&dir, RecursiveMode::NonRecursive)
+                .is_err()
+            {
+                return false;
+            }
+            self.dirs.insert(dir);
+        }
+        self.entries.entry(path).or_insert(Entry {
+            svg,
+            texture: None,
+            task: None,
+            unloaded: true,
+        });
+        true
+    }
+}
+
+/// Watch events carry absolute paths, make the watched ones match.
+pub(crate) fn absolute(path: &Path) -> PathBuf {
+    path.canonicalize()
+        .unwrap_or_else(|_| match std::env::current_dir() {
+            Ok(dir) => dir.join(path),
+            Err(_) => path.to_path_buf(),
+        })
+}
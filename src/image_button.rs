@@ -0,0 +1,207 @@
+use crate::FULL_UV;
+use egui::{
+    Align2, Color32, CursorIcon, Rect, Response, Sense, TextStyle, TextureId, Ui, Vec2, Widget,
+};
+
+/// Clickable image with optional label, with an image (or an automatic variant) per state.
+///
+/// Missing state images fall back to the normal one, darkened while pressed and faded while disabled,
+/// `Image::grayscale` makes a proper disabled variant.
+/// Focusable with Tab, Space and Enter click it like other egui buttons.
+///
+/// ```ignore
+/// if ui.add(ImageButton::new(texture_id, [32.0, 32.0]).text("Save")).clicked() { .. }
+/// ```
+pub struct ImageButton {
+    normal: TextureId,
+    hovered: Option<TextureId>,
+    pressed: Option<TextureId>,
+    selected_image: Option<TextureId>,
+    disabled: Option<TextureId>,
+    size: Vec2,
+    text: Option<String>,
+    selected: bool,
+    enabled: bool,
+    frame: bool,
+}
+
+impl ImageButton {
+    /// `size` of the image in points.
+    pub fn new(texture_id: TextureId, size: impl Into<Vec2>) -> Self {
+        Self {
+            normal: texture_id,
+            hovered: None,
+            pressed: None,
+            selected_image: None,
+            disabled: None,
+            size: size.into(),
+            text: None,
+            selected: false,
+            enabled: true,
+            frame: true,
+        }
+    }
+
+    pub fn hovered_image(mut self, texture_id: TextureId) -> Self {
+        self.hovered = Some(texture_id);
+        self
+    }
+
+    pub fn pressed_image(mut self, texture_id: TextureId) -> Self {
+        self.pressed = Some(texture_id);
+        self
+    }
+
+    pub fn selected_image(mut self, texture_id: TextureId) -> Self {
+        self.selected_image = Some(texture_id);
+        self
+    }
+
+    pub fn disabled_image(mut self, texture_id: TextureId) -> Self {
+        self.disabled = Some(texture_id);
+        self
+    }
+
+    /// Label right of the image.
+    pub fn text(mut self, text: impl ToString) -> Self {
+        self.text = Some(text.to_string());
+        self
+    }
+
+    /// Draw as selected, with the selection colors and `selected_image`.
+    pub fn selected(mut self, selected: bool) -> Self {
+        self.selected = selected;
+        self
+    }
+
+    /// A disabled button can't be clicked, `true` by default.
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    /// Background and border, `true` by default. Without it they're only shown on hover and selection.
+    pub fn frame(mut self, frame: bool) -> Self {
+        self.frame = frame;
+        self
+    }
+}
+
+impl Widget for ImageButton {
+    fn ui(self, ui: &mut Ui) -> Response {
+        let enabled = self.enabled && ui.is_enabled();
+        let padding = ui.spacing().button_padding;
+        let galley = self
+            .text
+            .map(|text| ui.fonts().layout_no_wrap(TextStyle::Button, text));
+        let mut desired_size = self.size + padding * 2.0;
+        if let Some(galley) = &galley {
+            desired_size.x += ui.spacing().item_spacing.x + galley.size.x;
+            desired_size.y = desired_size.y.max(galley.size.y + padding.y * 2.0);
+        }
+        let sense = if enabled {
+            Sense::click()
+        } else {
+            Sense::hover()
+        };
+        let (rect, response) = ui.allocate_exact_size(desired_size, sense);
+        if !ui.clip_rect().intersects(rect) {
+            return response;
+        }
+
+        let visuals = *ui.visuals().widgets.style(&response);
+        let selection = &ui.visuals().selection;
+        let painter = ui.painter();
+        let active = enabled && (response.hovered() || response.is_pointer_button_down_on());
+        if self.selected {
+            painter.rect_filled(rect, visuals.corner_radius, selection.bg_fill);
+            painter.rect_stroke(rect, visuals.corner_radius, selection.stroke);
+        } else if self.frame || active {
+            let frame = rect.expand(visuals.expansion);
+            painter.rect_filled(frame, visuals.corner_radius, visuals.bg_fill);
+            painter.rect_stroke(frame, visuals.corner_radius, visuals.bg_stroke);
+        }
+        if response.has_focus() {
+            painter.rect_stroke(rect.expand(2.0), visuals.corner_radius, selection.stroke);
+        }
+
+        let pressed = enabled && response.is_pointer_button_down_on();
+        let (texture_id, tint) = if !enabled {
+            match self.disabled {
+                Some(texture_id) => (texture_id, Color32::WHITE),
+                None => (
+                    self.normal,
+                    Color32::from_rgba_premultiplied(96, 96, 96, 96),
+                ),
+            }
+        } else if pressed {
+            match self.pressed {
+                Some(texture_id) => (texture_id, Color32::WHITE),
+                None => (self.normal, Color32::from_gray(180)),
+            }
+        } else {
+            let texture_id = if self.selected {
+                self.selected_image
+            } else if response.hovered() {
+                self.hovered
+            } else {
+                None
+            };
+            (texture_id.unwrap_or(self.normal), Color32::WHITE)
+        };
+        let content = rect.shrink2(padding);
+        let image_rect = Align2::LEFT_CENTER.align_size_within_rect(self.size, content);
+        painter.image(texture_id, image_rect, FULL_UV, tint);
+        if let Some(galley) = galley {
+            let text_rect = Align2::LEFT_CENTER.align_size_within_rect(
+                galley.size,
+                Rect::from_min_max(
+                    egui::pos2(
+                        image_rect.max.x + ui.spacing().item_spacing.x,
+                        content.min.y,
+                    ),
+                    content.max,
+                ),
+            );
+            let color = if self.selected {
+                selection.stroke.color
+            } else {
+                visuals.fg_stroke.color
+            };
+            painter.galley(text_rect.min, galley, color);
+        }
+
+        if enabled {
+            response.on_hover_cursor(CursorIcon::PointingHand)
+        } else {
+            response
+        }
+    }
+}
+
+/// `ImageButton` switching a `bool`, drawn as selected while `true`.
+///
+/// ```ignore
+/// ui.add(ImageToggle::new(&mut muted, ImageButton::new(icon, [24.0, 24.0]).selected_image(muted_icon)));
+/// ```
+pub struct ImageToggle<'a> {
+    value: &'a mut bool,
+    button: ImageButton,
+}
+
+impl<'a> ImageToggle<'a> {
+    pub fn new(value: &'a mut bool, button: ImageButton) -> Self {
+        Self { value, button }
+    }
+}
+
+impl<'a> Widget for ImageToggle<'a> {
+    fn ui(self, ui: &mut Ui) -> Response {
+        let mut response = self.button.selected(*self.value).ui(ui);
+        if response.clicked() {
+            *self.value = !*self.value;
+            response.mark_changed()
+        }
+        response
+    }
+}
@@ -0,0 +1,243 @@
+use crate::FULL_UV;
+use egui::{
+    lerp, pos2, vec2, Align2, Color32, Mesh, Painter, Pos2, Rect, Response, Sense, Shape, Stroke,
+    TextureId, Ui, Vec2, Widget,
+};
+use std::f32::consts::FRAC_PI_2;
+
+/// How an image is sized inside the widget rect, same as CSS `object-fit`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Fit {
+    /// Scale to fit inside keeping aspect ratio, may leave empty bands.
+    #[default]
+    Contain,
+    /// Scale to cover the whole rect keeping aspect ratio, the overflow is cropped.
+    Cover,
+    /// Stretch to the rect, ignoring aspect ratio.
+    Fill,
+    /// Native size, cropped if larger than the rect.
+    None,
+    /// Like `Contain` but never scaled up.
+    ScaleDown,
+}
+
+/// Screen rect and uv rect to draw an image of `image_size` inside `frame`, the image is cut to `frame`.
+pub(crate) fn fit_image(fit: Fit, align: Align2, image_size: Vec2, frame: Rect) -> (Rect, Rect) {
+    if image_size.x <= 0.0 || image_size.y <= 0.0 {
+        return (frame, FULL_UV);
+    }
+    let contain = (frame.width() / image_size.x).min(frame.height() / image_size.y);
+    let size = match fit {
+        Fit::Fill => return (frame, FULL_UV),
+        Fit::Contain => image_size * contain,
+        Fit::Cover => {
+            image_size * (frame.width() / image_size.x).max(frame.height() / image_size.y)
+        }
+        Fit::None => image_size,
+        Fit::ScaleDown => image_size * contain.min(1.0),
+    };
+    let rect = align.align_size_within_rect(size, frame);
+    let visible = rect.intersect(frame);
+    let uv = Rect::from_min_max(
+        pos2(
+            (visible.min.x - rect.min.x) / rect.width(),
+            (visible.min.y - rect.min.y) / rect.height(),
+        ),
+        pos2(
+            (visible.max.x - rect.min.x) / rect.width(),
+            (visible.max.y - rect.min.y) / rect.height(),
+        ),
+    );
+    (visible, uv)
+}
+
+/// Draw a texture with rounded corners, as a triangle fan with uvs following the position.
+pub(crate) fn paint_rounded_image(
+    painter: &Painter,
+    texture_id: TextureId,
+    rect: Rect,
+    uv: Rect,
+    corner_radius: f32,
+    tint: Color32,
+) {
+    let radius = corner_radius
+        .min(rect.width() * 0.5)
+        .min(rect.height() * 0.5);
+    if radius < 0.5 {
+        painter.image(texture_id, rect, uv, tint);
+        return;
+    }
+    let uv_at = |pos: Pos2| {
+        pos2(
+            lerp(uv.min.x..=uv.max.x, (pos.x - rect.min.x) / rect.width()),
+            lerp(uv.min.y..=uv.max.y, (pos.y - rect.min.y) / rect.height()),
+        )
+    };
+    let mut mesh = Mesh::with_texture(texture_id);
+    let mut add_vertex = |pos: Pos2| {
+        mesh.vertices.push(egui::epaint::Vertex {
+            pos,
+            uv: uv_at(pos),
+            color: tint,
+        })
+    };
+    add_vertex(rect.center());
+    // Clockwise in screen space, starting at the top left corner.
+    let corners = [
+        (pos2(rect.min.x + radius, rect.min.y + radius), 2.0),
+        (pos2(rect.max.x - radius, rect.min.y + radius), 3.0),
+        (pos2(rect.max.x - radius, rect.max.y - radius), 0.0),
+        (pos2(rect.min.x + radius, rect.max.y - radius), 1.0),
+    ];
+    const SEGMENTS: usize = 8;
+    for (center, quarter) in corners.iter() {
+        for i in 0..=SEGMENTS {
+            let angle = (quarter + i as f32 / SEGMENTS as f32) * FRAC_PI_2;
+            add_vertex(*center + radius * vec2(angle.cos(), angle.sin()))
+        }
+    }
+    let count = mesh.vertices.len() as u32 - 1;
+    for i in 0..count {
+        mesh.add_triangle(0, 1 + i, 1 + (i + 1) % count)
+    }
+    painter.add(Shape::mesh(mesh));
+}
+
+/// Image widget taking care of sizing, cropping and decoration.
+///
+/// ```ignore
+/// ui.add(ImageView::new(texture_id, image.size()).fit(Fit::Cover).size([96.0, 96.0]).corner_radius(48.0));
+/// ```
+pub struct ImageView {
+    texture_id: TextureId,
+    image_size: Vec2,
+    size: Option<Vec2>,
+    max_size: Vec2,
+    fit: Fit,
+    align: Align2,
+    corner_radius: f32,
+    border: Stroke,
+    background: Color32,
+    tint: Color32,
+    sense: Sense,
+}
+
+impl ImageView {
+    /// `image_size` is the native size of the texture, e.g. `Image::size`.
+    pub fn new(texture_id: TextureId, image_size: impl Into<Vec2>) -> Self {
+        Self {
+            texture_id,
+            image_size: image_size.into(),
+            size: None,
+            max_size: Vec2::INFINITY,
+            fit: Fit::Contain,
+            align: Align2::CENTER_CENTER,
+            corner_radius: 0.0,
+            border: Stroke::none(),
+            background: Color32::TRANSPARENT,
+            tint: Color32::WHITE,
+            sense: Sense::hover(),
+        }
+    }
+
+    /// Size of the widget, the native image size (limited by `max_size`) by default.
+    pub fn size(mut self, size: impl Into<Vec2>) -> Self {
+        self.size = Some(size.into());
+        self
+    }
+
+    /// Upper limit of the widget size, the image is scaled down keeping its aspect ratio to stay within.
+    pub fn max_size(mut self, max_size: impl Into<Vec2>) -> Self {
+        self.max_size = max_size.into();
+        self
+    }
+
+    /// `Fit::Contain` by default.
+    pub fn fit(mut self, fit: Fit) -> Self {
+        self.fit = fit;
+        self
+    }
+
+    /// Where the image sits inside the widget when it doesn't fill it, centered by default.
+    pub fn align(mut self, align: Align2) -> Self {
+        self.align = align;
+        self
+    }
+
+    pub fn corner_radius(mut self, corner_radius: f32) -> Self {
+        self.corner_radius = corner_radius;
+        self
+    }
+
+    /// Stroke around the widget rect.
+    pub fn border(mut self, border: impl Into<Stroke>) -> Self {
+        self.border = border.into();
+        self
+    }
+
+    /// Fill behind the image, visible around `Contain` and transparent images.
+    pub fn background(mut self, background: Color32) -> Self {
+        self.background = background;
+        self
+    }
+
+    /// Multiply the image colors, e.g. to dim it.
+    pub fn tint(mut self, tint: Color32) -> Self {
+        self.tint = tint;
+        self
+    }
+
+    /// Make the widget clickable, `Sense::hover()` by default.
+    pub fn sense(mut self, sense: Sense) -> Self {
+        self.sense = sense;
+        self
+    }
+
+    /// Size the widget allocates.
+    pub fn desired_size(&self) -> Vec2 {
+        match self.size {
+            Some(size) => size.min(self.max_size),
+            None => {
+                let size = self.image_size;
+                let scale = (self.max_size.x / size.x)
+                    .min(self.max_size.y / size.y)
+                    .min(1.0);
+                if scale.is_finite() {
+                    size * scale
+                } else {
+                    size.min(self.max_size)
+                }
+            }
+        }
+    }
+
+    /// Draw into `rect` without allocating, for custom layouts.
+    pub fn paint_at(&self, ui: &Ui, rect: Rect) {
+        let painter = ui.painter();
+        if self.background != Color32::TRANSPARENT {
+            painter.rect_filled(rect, self.corner_radius, self.background)
+        }
+        let (image_rect, uv) = fit_image(self.fit, self.align, self.image_size, rect);
+        paint_rounded_image(
+            painter,
+            self.texture_id,
+            image_rect,
+            uv,
+            self.corner_radius,
+            self.tint,
+        );
+        if self.border != Stroke::none() {
+            painter.rect_stroke(rect, self.corner_radius, self.border)
+        }
+    }
+}
+
+impl Widget for ImageView {
+    fn ui(self, ui: &mut Ui) -> Response {
+        let (rect, response) = ui.allocate_exact_size(self.desired_size(), self.sense);
+        if ui.clip_rect().intersects(rect) {
+            self.paint_at(ui, rect)
+        }
+        response
+    }
+}
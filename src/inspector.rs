@@ -0,0 +1,173 @@
+use crate::{unmultiply, Image, FULL_UV};
+use egui::{pos2, Color32, CursorIcon, Pos2, Rect, Response, Sense, Stroke, Ui};
+
+/// Above this many lines the grid isn't drawn.
+const MAX_GRID_LINES: usize = 512;
+
+/// Result of `PixelInspector::show`.
+pub struct Inspection {
+    /// The widget response, with the pixel tooltip attached.
+    pub response: Response,
+    /// `(x, y, color)` of the hovered pixel.
+    pub hovered: Option<(usize, usize, Color32)>,
+    /// Color clicked in eyedropper mode.
+    pub picked: Option<Color32>,
+}
+
+/// Overlay for any widget showing an `Image`: pixel grid at high zoom, hovered pixel tooltip and eyedropper.
+///
+/// ```ignore
+/// let response = ui.image(texture_id, size);
+/// let inspection = PixelInspector::new(&image).eyedropper(true).show(ui, response);
+/// if let Some(color) = inspection.picked { .. }
+/// ```
+pub struct PixelInspector<'a> {
+    image: &'a Image,
+    uv: Rect,
+    image_rect: Option<Rect>,
+    grid_from: f32,
+    eyedropper: bool,
+}
+
+impl<'a> PixelInspector<'a> {
+    /// `image` has to be the content of the texture shown by the widget.
+    pub fn new(image: &'a Image) -> Self {
+        Self {
+            image,
+            uv: FULL_UV,
+            image_rect: None,
+            grid_from: 8.0,
+            eyedropper: false,
+        }
+    }
+
+    /// Part of the image shown, for widgets drawing with a uv rect (e.g. `ImageView` with `Fit::Cover`).
+    pub fn uv(mut self, uv: Rect) -> Self {
+        self.uv = uv;
+        self
+    }
+
+    /// Screen rect of the image when it differs from the widget rect, e.g. `ImageViewer::image_rect()`.
+    pub fn image_rect(mut self, image_rect: Rect) -> Self {
+        self.image_rect = Some(image_rect);
+        self
+    }
+
+    /// Show the pixel grid once a pixel is at least this many points wide, 8.0 by default.
+    pub fn grid_from(mut self, points: f32) -> Self {
+        self.grid_from = points;
+        self
+    }
+
+    /// Pick the clicked pixel color, with a crosshair cursor.
+    pub fn eyedropper(mut self, eyedropper: bool) -> Self {
+        self.eyedropper = eyedropper;
+        self
+    }
+
+    /// Image pixel under a screen position of an image drawn at `image_rect`.
+    pub fn pixel_at(&self, image_rect: Rect, pos: Pos2) -> Option<(usize, usize)> {
+        if !image_rect.contains(pos) {
+            return None;
+        }
+        let u = self.uv.min.x + (pos.x - image_rect.min.x) / image_rect.width() * self.uv.width();
+        let v = self.uv.min.y + (pos.y - image_rect.min.y) / image_rect.height() * self.uv.height();
+        let x = (u * self.image.width() as f32).floor();
+        let y = (v * self.image.height() as f32).floor();
+        if x < 0.0
+            || y < 0.0
+            || x as usize >= self.image.width()
+            || y as usize >= self.image.height()
+        {
+            return None;
+        }
+        Some((x as usize, y as usize))
+    }
+
+    /// Paint the overlay over the widget of `response` and attach the pixel tooltip.
+    pub fn show(self, ui: &Ui, response: Response) -> Inspection {
+        let clip = response.rect;
+        let image_rect = self.image_rect.unwrap_or(clip);
+        let painter = ui.painter_at(clip);
+        let (width, height) = (self.image.width(), self.image.height());
+
+        // Screen position of a pixel boundary.
+        let screen_x = |x: usize| {
+            image_rect.min.x
+                + ((x as f32 / width as f32) - self.uv.min.x) / self.uv.width() * image_rect.width()
+        };
+        let screen_y = |y: usize| {
+            image_rect.min.y
+                + ((y as f32 / height as f32) - self.uv.min.y) / self.uv.height()
+                    * image_rect.height()
+        };
+
+        let pixel_width = screen_x(1) - screen_x(0);
+        let pixel_height = screen_y(1) - screen_y(0);
+        if width > 0 && height > 0 && pixel_width.min(pixel_height) >= self.grid_from {
+            let visible = image_rect.intersect(clip);
+            let first = self.pixel_at(image_rect, visible.min);
+            let last = self.pixel_at(image_rect, visible.max - egui::vec2(0.5, 0.5));
+            if let (Some((x0, y0)), Some((x1, y1))) = (first, last) {
+                if x1 - x0 + y1 - y0 <= MAX_GRID_LINES {
+                    let stroke = Stroke::new(1.0, Color32::from_black_alpha(96));
+                    for x in x0..=x1 + 1 {
+                        let x = screen_x(x);
+                        painter
+                            .line_segment([pos2(x, visible.min.y), pos2(x, visible.max.y)], stroke)
+                    }
+                    for y in y0..=y1 + 1 {
+                        let y = screen_y(y);
+                        painter
+                            .line_segment([pos2(visible.min.x, y), pos2(visible.max.x, y)], stroke)
+                    }
+                }
+            }
+        }
+
+        let response = if self.eyedropper {
+            response.interact(Sense::click())
+        } else {
+            response
+        };
+        let hovered = response
+            .hover_pos()
+            .filter(|pos| clip.contains(*pos))
+            .and_then(|pos| self.pixel_at(image_rect, pos))
+            .and_then(|(x, y)| self.image.get_pixel(x, y).map(|color| (x, y, color)));
+        let picked = match hovered {
+            Some((_, _, color)) if self.eyedropper && response.clicked() => Some(color),
+            _ => None,
+        };
+
+        let response = match hovered {
+            Some((x, y, color)) => {
+                let pixel = Rect::from_min_max(
+                    pos2(screen_x(x), screen_y(y)),
+                    pos2(screen_x(x + 1), screen_y(y + 1)),
+                );
+                if pixel.width() >= 4.0 {
+                    painter.rect_stroke(pixel, 0.0, Stroke::new(1.0, Color32::WHITE));
+                    painter.rect_stroke(pixel.expand(1.0), 0.0, Stroke::new(1.0, Color32::BLACK));
+                }
+                let [r, g, b, a] = unmultiply(color);
+                let text = format!(
+                    "x: {}, y: {}\nRGBA: {} {} {} {}\n#{:02X}{:02X}{:02X}{:02X}",
+                    x, y, r, g, b, a, r, g, b, a
+                );
+                let response = response.on_hover_text(text);
+                if self.eyedropper {
+                    response.on_hover_cursor(CursorIcon::Crosshair)
+                } else {
+                    response
+                }
+            }
+            None => response,
+        };
+        Inspection {
+            response,
+            hovered,
+            picked,
+        }
+    }
+}
@@ -1,31 +1,321 @@
 pub use asynchron;
-use asynchron::{Futurized};
-use egui::{Color32, TextureId};
-use epi;
+use egui::{
+    color::{gamma_u8_from_linear_f32, linear_f32_from_gamma_u8},
+    pos2, Color32, Rect, TextureId,
+};
+
+mod assets;
+mod atlas;
+mod async_image;
+mod compare;
+mod crop;
+#[cfg(feature = "image")]
+mod convert;
+mod dynamic_texture;
+mod gallery;
+mod hash;
+mod hdr;
+#[cfg(feature = "hot-reload")]
+mod hot_reload;
+mod image_button;
+mod image_view;
+mod inspector;
+mod loader;
+mod nine_slice;
+mod paint;
+mod static_image;
+mod sub_image;
+#[cfg(feature = "image")]
+mod thumbnail;
+mod viewer;
+pub use assets::AssetBundle;
+pub use atlas::{AtlasKey, TextureAtlas};
+pub use async_image::{AsyncImage, Placeholder};
+pub use compare::{CompareMode, ImageCompare, Split};
+pub use crop::CropTool;
+pub use dynamic_texture::{DynamicTexture, TextureUpdate};
+pub use gallery::{Gallery, GalleryResponse, SelectionMode};
+pub use hash::thumbhash_aspect_ratio;
+pub use hdr::{HdrImage, ToneMapping};
+#[cfg(feature = "hot-reload")]
+pub use hot_reload::HotReload;
+pub use image_button::{ImageButton, ImageToggle};
+pub use image_view::{Fit, ImageView};
+pub use inspector::{Inspection, PixelInspector};
+#[cfg(feature = "macros")]
+pub use egui_extras_lib_macros::include_image;
+#[cfg(feature = "static-image")]
+pub use egui_extras_lib_macros::include_static_image;
+pub use loader::{ImageLoader, ImageSource, ImageTask, LoadProgress, Priority};
+pub use nine_slice::{Insets, NineSlice, SliceFill};
+pub use paint::{paint_background, paint_tiled};
+pub use static_image::StaticImage;
+pub use sub_image::SubImage;
+#[cfg(feature = "image")]
+pub use thumbnail::{ThumbnailGenerator, ThumbnailMode, ThumbnailSource};
+pub use viewer::{ImageViewer, ViewTransform};
 
 extern "Rust" {
-    fn _image_from_bytes(bytes: &[u8]) -> Option<Image>;
-    fn _svg_from_bytes(bytes: &[u8]) -> Option<Image>;
-    fn _tex_id_from_image(image: &Image, frame: &mut epi::Frame<'_>) -> TextureId;
-    fn _load_image(path: String) -> Futurized<(),Image>;
-    fn _load_svg(path: String) -> Futurized<(),Image>;
+    fn _image_from_bytes(bytes: &[u8]) -> Option<NativeImage>;
+    fn _svg_from_bytes(bytes: &[u8]) -> Option<NativeImage>;
+    fn _tex_id_from_image(image: &NativeImage, frame: &mut epi::Frame<'_>) -> TextureId;
+}
+
+/// `Image` as laid out by the native library.
+#[derive(Clone, Default)]
+struct NativeImage {
+    size: (f32, f32),
+    pixels: Vec<Color32>,
+}
+
+impl From<NativeImage> for Image {
+    fn from(image: NativeImage) -> Self {
+        let (width, height) = (image.size.0.round() as usize, image.size.1.round() as usize);
+        Image::from_parts(width, height, image.pixels)
+    }
+}
+
+/// UV rect covering a whole texture.
+pub(crate) const FULL_UV: Rect = Rect::from_min_max(pos2(0.0, 0.0), pos2(1.0, 1.0));
+
+/// How the color channels of raw RGBA bytes relate to the alpha channel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AlphaMode {
+    /// Straight alpha, what most image files store.
+    Unmultiplied,
+    /// Color channels already multiplied by alpha (in linear space, the same way egui does).
+    Premultiplied,
 }
 
 /// Example available on repository: https://github.com/Ar37-rs/egui-extras-lib
 #[derive(Clone, Default)]
 pub struct Image {
-    pub size: (f32, f32),
-    pub pixels: Vec<Color32>,
+    width: usize,
+    height: usize,
+    /// Float size and sRGBA pixels with premultiplied alpha (what egui expects), row-major,
+    /// kept in the native layout so uploading doesn't copy. Always `width * height` pixels.
+    native: NativeImage,
 }
 
 impl Image {
+    /// Pixels are padded with transparent ones or cut off to hold exactly `width * height`.
+    pub(crate) fn from_parts(width: usize, height: usize, mut pixels: Vec<Color32>) -> Image {
+        pixels.resize(width * height, Color32::TRANSPARENT);
+        Image {
+            width,
+            height,
+            native: NativeImage {
+                size: (width as f32, height as f32),
+                pixels,
+            },
+        }
+    }
+
     /// New image form bytes of .png, .gif, .jpg and .etc which supported by image crate.
+    ///
+    /// Pixels are premultiplied the way egui expects either way: with the `image` feature decoding is done in this
+    /// crate through `Image::from_rgba_unmultiplied`, without it the native library decodes and premultiplies every
+    /// pixel with `Color32::from_rgba_unmultiplied`.
     pub fn new(bytes: &[u8]) -> Option<Image> {
+        #[cfg(feature = "image")]
+        {
+            image::load_from_memory(bytes).ok().map(Image::from)
+        }
+        #[cfg(not(feature = "image"))]
         unsafe {
-            _image_from_bytes(bytes)
+            _image_from_bytes(bytes).map(Image::from)
+        }
+    }
+
+    /// New image from raw RGBA bytes, `None` if bytes length doesn't match `width * height * 4`.
+    pub fn from_rgba(width: usize, height: usize, rgba: &[u8], alpha: AlphaMode) -> Option<Image> {
+        if rgba.len() != width * height * 4 {
+            return None;
+        }
+        let pixels = rgba
+            .chunks_exact(4)
+            .map(|p| match alpha {
+                AlphaMode::Unmultiplied => Color32::from_rgba_unmultiplied(p[0], p[1], p[2], p[3]),
+                AlphaMode::Premultiplied => {
+                    Color32::from_rgba_premultiplied(p[0], p[1], p[2], p[3])
+                }
+            })
+            .collect();
+        Some(Image::from_parts(width, height, pixels))
+    }
+
+    /// New image from straight (unmultiplied) alpha RGBA bytes, premultiplied on the way in.
+    pub fn from_rgba_unmultiplied(width: usize, height: usize, rgba: &[u8]) -> Option<Image> {
+        Image::from_rgba(width, height, rgba, AlphaMode::Unmultiplied)
+    }
+
+    /// New image from premultiplied alpha RGBA bytes, taken as is.
+    pub fn from_rgba_premultiplied(width: usize, height: usize, rgba: &[u8]) -> Option<Image> {
+        Image::from_rgba(width, height, rgba, AlphaMode::Premultiplied)
+    }
+
+    /// New image from 8-bit RGBA bytes (straight alpha), `None` if bytes length doesn't match the size.
+    pub fn from_rgba8(width: usize, height: usize, rgba: &[u8]) -> Option<Image> {
+        Image::from_rgba_unmultiplied(width, height, rgba)
+    }
+
+    /// New opaque image from 8-bit RGB bytes, `None` if bytes length doesn't match the size.
+    pub fn from_rgb8(width: usize, height: usize, rgb: &[u8]) -> Option<Image> {
+        if rgb.len() != width * height * 3 {
+            return None;
+        }
+        let pixels = rgb
+            .chunks_exact(3)
+            .map(|p| Color32::from_rgb(p[0], p[1], p[2]))
+            .collect();
+        Some(Image::from_parts(width, height, pixels))
+    }
+
+    /// New opaque image from 8-bit grayscale bytes, `None` if bytes length doesn't match the size.
+    pub fn from_gray8(width: usize, height: usize, gray: &[u8]) -> Option<Image> {
+        if gray.len() != width * height {
+            return None;
+        }
+        let pixels = gray.iter().map(|l| Color32::from_gray(*l)).collect();
+        Some(Image::from_parts(width, height, pixels))
+    }
+
+    /// New image by calling `f(x, y)` for every pixel, colors are premultiplied like any other `Color32`.
+    pub fn from_fn(
+        width: usize,
+        height: usize,
+        mut f: impl FnMut(usize, usize) -> Color32,
+    ) -> Image {
+        let mut pixels = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                pixels.push(f(x, y))
+            }
+        }
+        Image::from_parts(width, height, pixels)
+    }
+
+    /// New image from premultiplied pixels (row-major), `None` if pixels length doesn't match the size.
+    pub fn from_pixels(width: usize, height: usize, pixels: Vec<Color32>) -> Option<Image> {
+        if pixels.len() != width * height {
+            return None;
+        }
+        Some(Image::from_parts(width, height, pixels))
+    }
+
+    /// Width in pixels.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Height in pixels.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Pixel dimensions as float, convenient for `ui.image`.
+    pub fn size(&self) -> (f32, f32) {
+        self.native.size
+    }
+
+    /// sRGBA pixels with premultiplied alpha (what egui expects), row-major.
+    pub fn pixels(&self) -> &[Color32] {
+        &self.native.pixels
+    }
+
+    /// Mutable pixels, the number of pixels can't change.
+    pub fn pixels_mut(&mut self) -> &mut [Color32] {
+        &mut self.native.pixels
+    }
+
+    /// Pixel at `x`, `y`, `None` if out of bounds.
+    pub fn get_pixel(&self, x: usize, y: usize) -> Option<Color32> {
+        if x >= self.width() || y >= self.height() {
+            return None;
+        }
+        self.native.pixels.get(y * self.width() + x).copied()
+    }
+
+    /// Set pixel at `x`, `y`, out of bounds coordinates are ignored.
+    pub fn put_pixel(&mut self, x: usize, y: usize, color: Color32) {
+        if x >= self.width() || y >= self.height() {
+            return;
+        }
+        let width = self.width();
+        if let Some(pixel) = self.native.pixels.get_mut(y * width + x) {
+            *pixel = color
+        }
+    }
+
+    /// Iterator over rows of pixels, top to bottom.
+    pub fn rows(&self) -> std::slice::ChunksExact<'_, Color32> {
+        self.native.pixels.chunks_exact(self.width().max(1))
+    }
+
+    /// Mutable iterator over rows of pixels, top to bottom.
+    pub fn rows_mut(&mut self) -> std::slice::ChunksExactMut<'_, Color32> {
+        let width = self.width().max(1);
+        self.native.pixels.chunks_exact_mut(width)
+    }
+
+    /// View into a region of this image without copying, `None` if the region doesn't fit.
+    pub fn view(&self, x: usize, y: usize, width: usize, height: usize) -> Option<SubImage<'_>> {
+        SubImage::new(self, x, y, width, height)
+    }
+
+    /// Copy a region of this image into a new image, `None` if the region doesn't fit.
+    pub fn crop(&self, x: usize, y: usize, width: usize, height: usize) -> Option<Image> {
+        self.view(x, y, width, height).map(|view| view.to_image())
+    }
+
+    /// Size in points to show the image at its native resolution, use `ctx.pixels_per_point()`.
+    pub fn display_size(&self, pixels_per_point: f32) -> egui::Vec2 {
+        egui::vec2(
+            self.width() as f32 / pixels_per_point,
+            self.height() as f32 / pixels_per_point,
+        )
+    }
+
+    /// Pixels as straight (unmultiplied) alpha RGBA bytes, e.g. for saving to a file.
+    pub fn to_rgba_unmultiplied(&self) -> Vec<u8> {
+        self.native.pixels.iter().flat_map(|p| unmultiply(*p)).collect()
+    }
+
+    /// Pixels as premultiplied alpha RGBA bytes.
+    pub fn to_rgba_premultiplied(&self) -> Vec<u8> {
+        self.native.pixels.iter().flat_map(|p| p.to_array()).collect()
+    }
+
+    /// Reinterpret pixels that were filled with straight alpha values as unmultiplied and premultiply them,
+    /// fixes dark or bright fringes from sources that didn't premultiply.
+    pub fn premultiply(&mut self) {
+        for p in &mut self.native.pixels {
+            *p = Color32::from_rgba_unmultiplied(p.r(), p.g(), p.b(), p.a())
         }
     }
 
+    /// Grayscale copy keeping alpha, e.g. for a disabled `ImageButton`.
+    pub fn grayscale(&self) -> Image {
+        let pixels = self
+            .native
+            .pixels
+            .iter()
+            .map(|p| {
+                let luma = (p.r() as u32 * 299 + p.g() as u32 * 587 + p.b() as u32 * 114) / 1000;
+                Color32::from_rgba_premultiplied(luma as u8, luma as u8, luma as u8, p.a())
+            })
+            .collect();
+        Image::from_parts(self.width, self.height, pixels)
+    }
+
+    /// New image from bytes of high bit-depth or HDR files (16-bit .png/.tiff, .hdr, .exr), tone mapped for display.
+    ///
+    /// use `HdrImage::new` instead to keep the linear `f32` data around. Needs the `image` feature.
+    #[cfg(feature = "image")]
+    pub fn new_hdr(bytes: &[u8], tone_mapping: ToneMapping) -> Option<Image> {
+        HdrImage::new(bytes).map(|hdr| hdr.to_image(tone_mapping))
+    }
+
     /// if task id == 0 it means loading image (png, jpg, gif .etc)
     ///
     /// else if task_id == 1 loading svg image
@@ -34,30 +324,43 @@ impl Image {
     }
 
     /// New image form bytes of SVG v1.1 file specification which fully supported by usvg crate.
+    ///
+    /// The native library demultiplies the tiny-skia output (premultiplied in gamma space) and premultiplies it again
+    /// with `Color32::from_rgba_unmultiplied`, so pixels come out premultiplied the way egui expects.
     pub fn new_from_svg(bytes: &[u8]) -> Option<Image> {
-        unsafe {
-            _svg_from_bytes(bytes)
-        }
+        unsafe { _svg_from_bytes(bytes).map(Image::from) }
     }
 
     /// Image texture id.
     pub fn texture_id(&self, frame: &mut epi::Frame<'_>) -> TextureId {
-        unsafe {
-            _tex_id_from_image(self, frame)
-        }
+        unsafe { _tex_id_from_image(&self.native, frame) }
     }
 
-    /// Image loader (.png, .gif, .jpg and .etc ) on top of image crate.
-    pub fn load_image(path: String) -> Futurized<(),Image> {
-        unsafe {
-            _load_image(path)
-        }
+    /// Image loader (.png, .gif, .jpg and .etc ) on top of image crate, queued on `ImageLoader::global()`.
+    ///
+    /// `ImageTask` has the same `id`, `try_resolve`, `is_done` and `cancel` as `Futurized<(), Image>`,
+    /// and no thread of its own. Dropping it cancels the load if it's still queued or running.
+    pub fn load_image(path: String) -> ImageTask {
+        ImageLoader::global().load_image(path)
     }
 
-    /// SVG loader usvg, resvg, tiny-skia and image crates under the hood.
-    pub fn load_svg(path: String) -> Futurized<(),Image> {
-        unsafe {
-            _load_svg(path)
+    /// SVG loader usvg, resvg, tiny-skia and image crates under the hood, queued on `ImageLoader::global()`.
+    ///
+    /// Dropping the returned `ImageTask` cancels the load.
+    pub fn load_svg(path: String) -> ImageTask {
+        ImageLoader::global().load_svg(path)
+    }
+}
+
+/// Inverse of `Color32::from_rgba_unmultiplied`.
+pub(crate) fn unmultiply(color: Color32) -> [u8; 4] {
+    match color.a() {
+        0 => [0, 0, 0, 0],
+        255 => color.to_array(),
+        a => {
+            let a_lin = a as f32 / 255.0;
+            let channel = |c: u8| gamma_u8_from_linear_f32(linear_f32_from_gamma_u8(c) / a_lin);
+            [channel(color.r()), channel(color.g()), channel(color.b()), a]
         }
     }
 }
@@ -0,0 +1,726 @@
+use crate::Image;
+use asynchron::Progress;
+#[cfg(feature = "hot-reload")]
+use notify::{DebouncedEvent, RecommendedWatcher, RecursiveMode, Watcher};
+use once_cell::sync::Lazy;
+#[cfg(feature = "hot-reload")]
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    sync::{mpsc::channel, Weak},
+    time::Duration,
+};
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap, VecDeque},
+    fs::File,
+    hash::{Hash, Hasher},
+    io::{ErrorKind, Read},
+    sync::{
+        atomic::{AtomicBool, AtomicU8, Ordering},
+        Arc, Condvar, Mutex,
+    },
+    thread,
+};
+
+/// Task id of `ImageTask` loading an image (png, jpg, gif .etc), see `Image::type_id`.
+pub(crate) const IMAGE: usize = 0;
+/// Task id of `ImageTask` loading an svg image, see `Image::type_id`.
+pub(crate) const SVG: usize = 1;
+
+static GLOBAL: Lazy<ImageLoader> = Lazy::new(|| {
+    let threads = thread::available_parallelism().map_or(4, |n| n.get());
+    ImageLoader::new(threads)
+});
+
+/// Order in which queued jobs are picked up by the workers, jobs of equal priority run first come first served.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Priority {
+    /// e.g. images scrolled out of view.
+    Low,
+    #[default]
+    Normal,
+    /// e.g. images currently visible.
+    High,
+}
+
+impl Priority {
+    fn from_u8(value: u8) -> Self {
+        match value {
+            0 => Priority::Low,
+            1 => Priority::Normal,
+            _ => Priority::High,
+        }
+    }
+}
+
+/// What a loader job is currently doing, passed as `Progress::Current(Some(..))` by `ImageTask::try_resolve`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LoadProgress {
+    /// Waiting for a free worker.
+    Queued,
+    /// Reading the file or downloading the url, `total` is `None` when the size isn't known up front.
+    Reading { read: u64, total: Option<u64> },
+    /// Decoding an image (png, jpg, gif .etc).
+    Decoding,
+    /// Rasterizing an svg, the native rasterizer renders in one go and doesn't report its progress.
+    Rasterizing,
+}
+
+impl LoadProgress {
+    /// Rough overall completion 0.0..=1.0 for progress bars, reading counts as the first half.
+    pub fn fraction(&self) -> f32 {
+        match *self {
+            LoadProgress::Queued => 0.0,
+            LoadProgress::Reading { read, total } => match total {
+                Some(total) if total > 0 => 0.5 * (read as f32 / total as f32).min(1.0),
+                _ => 0.0,
+            },
+            LoadProgress::Decoding | LoadProgress::Rasterizing => 0.5,
+        }
+    }
+}
+
+enum State {
+    Pending,
+    Ready(Result<Image, String>),
+    Resolved,
+}
+
+struct TaskState {
+    state: Mutex<State>,
+    canceled: AtomicBool,
+    priority: AtomicU8,
+    /// Shared with the job and every other handle waiting for it.
+    progress: Arc<Mutex<LoadProgress>>,
+}
+
+impl TaskState {
+    fn priority(&self) -> Priority {
+        Priority::from_u8(self.priority.load(Ordering::SeqCst))
+    }
+}
+
+/// Where an image is loaded from.
+#[derive(Clone, Debug)]
+pub enum ImageSource {
+    /// File on disk.
+    Path(String),
+    /// Encoded file content already in memory.
+    Bytes(Arc<[u8]>),
+    /// http(s) url, downloaded on the worker (`network` feature).
+    #[cfg(feature = "network")]
+    Url(String),
+}
+
+impl From<String> for ImageSource {
+    fn from(path: String) -> Self {
+        ImageSource::Path(path)
+    }
+}
+
+impl From<&str> for ImageSource {
+    fn from(path: &str) -> Self {
+        ImageSource::Path(path.to_string())
+    }
+}
+
+impl From<Vec<u8>> for ImageSource {
+    fn from(bytes: Vec<u8>) -> Self {
+        ImageSource::Bytes(bytes.into())
+    }
+}
+
+impl From<Arc<[u8]>> for ImageSource {
+    fn from(bytes: Arc<[u8]>) -> Self {
+        ImageSource::Bytes(bytes)
+    }
+}
+
+impl ImageSource {
+    /// Shown by `Progress::Error` when loading fails.
+    fn describe(&self) -> String {
+        match self {
+            ImageSource::Path(path) => path.clone(),
+            ImageSource::Bytes(bytes) => format!("<{} bytes>", bytes.len()),
+            #[cfg(feature = "network")]
+            ImageSource::Url(url) => url.clone(),
+        }
+    }
+
+    /// Whether a job loading `self` can be joined by a request for `other`, bytes are compared
+    /// in full since `JobKey` only keeps their hash.
+    fn same_as(&self, other: &ImageSource) -> bool {
+        match (self, other) {
+            (ImageSource::Path(a), ImageSource::Path(b)) => a == b,
+            (ImageSource::Bytes(a), ImageSource::Bytes(b)) => Arc::ptr_eq(a, b) || a == b,
+            #[cfg(feature = "network")]
+            (ImageSource::Url(a), ImageSource::Url(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+/// Identity of a job, requests with equal keys share a single load once their sources compare equal.
+///
+/// The task id (image or svg) is the only render option, it's part of every key.
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum JobKey {
+    Path(usize, String),
+    Bytes(usize, u64, usize),
+    #[cfg(feature = "network")]
+    Url(usize, String),
+}
+
+impl JobKey {
+    fn new(id: usize, source: &ImageSource) -> Self {
+        match source {
+            ImageSource::Path(path) => JobKey::Path(id, path.clone()),
+            ImageSource::Bytes(bytes) => {
+                let mut hasher = DefaultHasher::new();
+                bytes.hash(&mut hasher);
+                JobKey::Bytes(id, hasher.finish(), bytes.len())
+            }
+            #[cfg(feature = "network")]
+            ImageSource::Url(url) => JobKey::Url(id, url.clone()),
+        }
+    }
+}
+
+struct Job {
+    id: usize,
+    key: JobKey,
+    source: ImageSource,
+    progress: Arc<Mutex<LoadProgress>>,
+    /// Every handle waiting for this job, the result is handed to all of them.
+    waiters: Mutex<Vec<Arc<TaskState>>>,
+    /// Reload of a watched file for handles that already got an image, failures aren't handed over.
+    reload: bool,
+}
+
+impl Job {
+    /// Canceled once every waiting handle is canceled.
+    fn is_canceled(&self) -> bool {
+        let waiters = self.waiters.lock().unwrap();
+        waiters
+            .iter()
+            .all(|task| task.canceled.load(Ordering::SeqCst))
+    }
+
+    /// Highest priority of the handles still interested in the result.
+    fn priority(&self) -> Priority {
+        let waiters = self.waiters.lock().unwrap();
+        waiters
+            .iter()
+            .filter(|task| !task.canceled.load(Ordering::SeqCst))
+            .map(|task| task.priority())
+            .max()
+            .unwrap_or(Priority::Low)
+    }
+}
+
+#[derive(Default)]
+struct Queue {
+    jobs: VecDeque<Arc<Job>>,
+    /// Jobs queued or being loaded, by key.
+    in_flight: HashMap<JobKey, Arc<Job>>,
+}
+
+impl Queue {
+    fn remove_in_flight(&mut self, job: &Arc<Job>) {
+        if let Some(current) = self.in_flight.get(&job.key) {
+            if Arc::ptr_eq(current, job) {
+                self.in_flight.remove(&job.key);
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct Shared {
+    queue: Mutex<Queue>,
+    available: Condvar,
+    shutdown: AtomicBool,
+}
+
+struct Workers {
+    shared: Arc<Shared>,
+    max_threads: usize,
+    threads: Mutex<usize>,
+    #[cfg(feature = "hot-reload")]
+    watch: Mutex<Option<Watch>>,
+}
+
+/// Files loaded through a watching loader, see `ImageLoader::watch`.
+#[cfg(feature = "hot-reload")]
+struct Watch {
+    watcher: RecommendedWatcher,
+    dirs: HashSet<PathBuf>,
+    /// Handles by absolute path, with their task id and the path they were loaded with.
+    files: HashMap<PathBuf, Vec<(usize, String, Weak<TaskState>)>>,
+}
+
+impl Drop for Workers {
+    fn drop(&mut self) {
+        self.shared.shutdown.store(true, Ordering::SeqCst);
+        self.shared.available.notify_all()
+    }
+}
+
+/// Loads images on a bounded pool of worker threads fed by a job queue.
+///
+/// `Image::load_image` and `Image::load_svg` go through a shared global loader,
+/// create your own if you need a different number of threads.
+///
+/// Concurrent requests for the same source (same path, url or bytes) and type are coalesced
+/// into a single load whose result is handed to every handle, the type is the only render option.
+#[derive(Clone)]
+pub struct ImageLoader {
+    workers: Arc<Workers>,
+}
+
+impl ImageLoader {
+    /// New loader running at most `threads` worker threads, spawned on demand.
+    pub fn new(threads: usize) -> Self {
+        Self {
+            workers: Arc::new(Workers {
+                shared: Arc::new(Shared::default()),
+                max_threads: threads.max(1),
+                threads: Mutex::new(0),
+                #[cfg(feature = "hot-reload")]
+                watch: Mutex::new(None),
+            }),
+        }
+    }
+
+    /// Loader used by `Image::load_image` and `Image::load_svg`, one thread per cpu core.
+    pub fn global() -> &'static ImageLoader {
+        &GLOBAL
+    }
+
+    /// Maximum number of worker threads.
+    pub fn threads(&self) -> usize {
+        self.workers.max_threads
+    }
+
+    /// Number of jobs waiting for a free worker.
+    pub fn queued(&self) -> usize {
+        self.workers.shared.queue.lock().unwrap().jobs.len()
+    }
+
+    /// Queue loading an image (.png, .gif, .jpg and .etc) from a path, url or bytes with `Priority::Normal`.
+    pub fn load_image(&self, source: impl Into<ImageSource>) -> ImageTask {
+        self.submit(IMAGE, source.into(), Priority::Normal)
+    }
+
+    /// Queue loading and rasterizing an svg from a path, url or bytes with `Priority::Normal`.
+    pub fn load_svg(&self, source: impl Into<ImageSource>) -> ImageTask {
+        self.submit(SVG, source.into(), Priority::Normal)
+    }
+
+    /// Same as `load_image` with the given priority.
+    pub fn load_image_with_priority(
+        &self,
+        source: impl Into<ImageSource>,
+        priority: Priority,
+    ) -> ImageTask {
+        self.submit(IMAGE, source.into(), priority)
+    }
+
+    /// Same as `load_svg` with the given priority.
+    pub fn load_svg_with_priority(
+        &self,
+        source: impl Into<ImageSource>,
+        priority: Priority,
+    ) -> ImageTask {
+        self.submit(SVG, source.into(), priority)
+    }
+
+    /// Queue a new job even if one for the same source is in flight, e.g. the file changed since it started.
+    #[cfg(feature = "hot-reload")]
+    pub(crate) fn reload(&self, id: usize, source: ImageSource, priority: Priority) -> ImageTask {
+        self.queue(id, source, priority, false)
+    }
+
+    /// Reload files loaded through this loader whenever they change on disk,
+    /// events for the same file within `debounce` are merged into one reload.
+    ///
+    /// Only paths loaded after this call are watched. Keep calling `try_resolve` on a handle to get
+    /// the reloaded image as another `Progress::Completed`, a reload that fails (e.g. a half written file)
+    /// is skipped. `false` if the platform watcher (inotify on linux) can't be created.
+    #[cfg(feature = "hot-reload")]
+    pub fn watch(&self, debounce: Duration) -> bool {
+        let mut watch = self.workers.watch.lock().unwrap();
+        if watch.is_some() {
+            return true;
+        }
+        let (tx, events) = channel();
+        let watcher = match notify::watcher(tx, debounce) {
+            Ok(watcher) => watcher,
+            Err(_) => return false,
+        };
+        let workers = Arc::downgrade(&self.workers);
+        // Ends with the loader, dropping the watcher closes the channel.
+        let spawned = thread::Builder::new()
+            .name("egui_extras_lib image watcher".to_string())
+            .spawn(move || {
+                for event in events {
+                    let changed = match event {
+                        DebouncedEvent::Create(path)
+                        | DebouncedEvent::Write(path)
+                        | DebouncedEvent::Rename(_, path) => Some(path),
+                        // Watch may be lost (e.g. queue overflow), reload everything.
+                        DebouncedEvent::Rescan => None,
+                        _ => continue,
+                    };
+                    match workers.upgrade() {
+                        Some(workers) => ImageLoader { workers }.reload_changed(changed.as_deref()),
+                        None => return,
+                    }
+                }
+            });
+        if spawned.is_err() {
+            return false;
+        }
+        *watch = Some(Watch {
+            watcher,
+            dirs: HashSet::new(),
+            files: HashMap::new(),
+        });
+        true
+    }
+
+    /// Remember a handle loaded from `path` if this loader is watching.
+    #[cfg(feature = "hot-reload")]
+    fn watch_file(&self, id: usize, path: String, task: &Arc<TaskState>) {
+        let mut watch = self.workers.watch.lock().unwrap();
+        let watch = match watch.as_mut() {
+            Some(watch) => watch,
+            None => return,
+        };
+        let file = crate::hot_reload::absolute(Path::new(&path));
+        if let Some(dir) = file.parent() {
+            if !watch.dirs.contains(dir) {
+                if watch
+                    .watcher
+                    .watch(dir, RecursiveMode::NonRecursive)
+                    .is_err()
+                {
+                    return;
+                }
+                watch.dirs.insert(dir.to_path_buf());
+            }
+        }
+        let handles = watch.files.entry(file).or_default();
+        handles.retain(|(_, _, task)| task.strong_count() > 0);
+        handles.push((id, path, Arc::downgrade(task)))
+    }
+
+    /// Queue reloads of the watched file at `changed` (every watched file if `None`) for the handles still alive.
+    #[cfg(feature = "hot-reload")]
+    fn reload_changed(&self, changed: Option<&Path>) {
+        let mut reloads: HashMap<(usize, String), Vec<Arc<TaskState>>> = HashMap::new();
+        if let Some(watch) = self.workers.watch.lock().unwrap().as_mut() {
+            watch.files.retain(|file, handles| {
+                handles.retain(|(_, _, task)| task.strong_count() > 0);
+                if changed.map_or(true, |changed| changed == file) {
+                    for (id, path, task) in handles.iter() {
+                        if let Some(task) = task.upgrade() {
+                            if !task.canceled.load(Ordering::SeqCst) {
+                                reloads.entry((*id, path.clone())).or_default().push(task)
+                            }
+                        }
+                    }
+                }
+                !handles.is_empty()
+            });
+        }
+        for ((id, path), waiters) in reloads {
+            let source = ImageSource::Path(path);
+            let job = Arc::new(Job {
+                id,
+                key: JobKey::new(id, &source),
+                source,
+                progress: Arc::new(Mutex::new(LoadProgress::Queued)),
+                waiters: Mutex::new(waiters),
+                reload: true,
+            });
+            // Not in `in_flight`, new handles can't join a job that may not hand them anything.
+            self.workers
+                .shared
+                .queue
+                .lock()
+                .unwrap()
+                .jobs
+                .push_back(job);
+            self.spawn_worker();
+            self.workers.shared.available.notify_one();
+        }
+    }
+
+    fn submit(&self, id: usize, source: ImageSource, priority: Priority) -> ImageTask {
+        #[cfg(feature = "hot-reload")]
+        let watched = match &source {
+            ImageSource::Path(path) => Some(path.clone()),
+            _ => None,
+        };
+        let task = self.queue(id, source, priority, true);
+        #[cfg(feature = "hot-reload")]
+        if let Some(path) = watched {
+            self.watch_file(id, path, &task.task)
+        }
+        task
+    }
+
+    fn queue(&self, id: usize, source: ImageSource, priority: Priority, join: bool) -> ImageTask {
+        let new_task = |progress| {
+            Arc::new(TaskState {
+                state: Mutex::new(State::Pending),
+                canceled: AtomicBool::new(false),
+                priority: AtomicU8::new(priority as u8),
+                progress,
+            })
+        };
+        let key = JobKey::new(id, &source);
+        let shared = &self.workers.shared;
+        let mut queue = shared.queue.lock().unwrap();
+        let joinable = queue
+            .in_flight
+            .get(&key)
+            .filter(|job| join && !job.is_canceled() && job.source.same_as(&source));
+        let task;
+        if let Some(job) = joinable {
+            task = new_task(job.progress.clone());
+            job.waiters.lock().unwrap().push(task.clone());
+        } else {
+            let progress = Arc::new(Mutex::new(LoadProgress::Queued));
+            task = new_task(progress.clone());
+            let job = Arc::new(Job {
+                id,
+                key: key.clone(),
+                source,
+                progress,
+                waiters: Mutex::new(vec![task.clone()]),
+                reload: false,
+            });
+            queue.in_flight.insert(key, job.clone());
+            queue.jobs.push_back(job);
+            drop(queue);
+            self.spawn_worker();
+            shared.available.notify_one();
+        }
+        ImageTask { id, task }
+    }
+
+    fn spawn_worker(&self) {
+        let mut threads = self.workers.threads.lock().unwrap();
+        if *threads >= self.workers.max_threads {
+            return;
+        }
+        let shared = self.workers.shared.clone();
+        let spawned = thread::Builder::new()
+            .name("egui_extras_lib image loader".to_string())
+            .spawn(move || worker(shared));
+        if spawned.is_ok() {
+            *threads += 1
+        }
+    }
+}
+
+fn worker(shared: Arc<Shared>) {
+    loop {
+        let job = {
+            let mut queue = shared.queue.lock().unwrap();
+            loop {
+                if shared.shutdown.load(Ordering::SeqCst) {
+                    return;
+                }
+                // Canceled jobs (e.g. dropped handles) are discarded without being loaded.
+                let (canceled, jobs): (VecDeque<_>, VecDeque<_>) =
+                    queue.jobs.drain(..).partition(|job| job.is_canceled());
+                queue.jobs = jobs;
+                for job in &canceled {
+                    queue.remove_in_flight(job);
+                    if job.reload {
+                        continue;
+                    }
+                    // Resolved as `Progress::Canceled` by `try_resolve`, instead of pending forever.
+                    for task in job.waiters.lock().unwrap().iter() {
+                        *task.state.lock().unwrap() = State::Ready(Err(job.source.describe()));
+                    }
+                }
+                if let Some(job) = pop_highest_priority(&mut queue.jobs) {
+                    break job;
+                }
+                queue = shared.available.wait(queue).unwrap();
+            }
+        };
+        let result = load(&job).ok_or_else(|| job.source.describe());
+        // No handle can join once the job left `in_flight`, so every waiter gets the result.
+        shared.queue.lock().unwrap().remove_in_flight(&job);
+        // A failed reload keeps the image the handles already got.
+        if job.reload && result.is_err() {
+            continue;
+        }
+        for task in job.waiters.lock().unwrap().iter() {
+            *task.state.lock().unwrap() = State::Ready(result.clone());
+        }
+    }
+}
+
+fn pop_highest_priority(jobs: &mut VecDeque<Arc<Job>>) -> Option<Arc<Job>> {
+    let mut best: Option<(usize, Priority)> = None;
+    for (i, job) in jobs.iter().enumerate() {
+        let priority = job.priority();
+        let better = match best {
+            Some((_, best)) => priority > best,
+            None => true,
+        };
+        if better {
+            best = Some((i, priority))
+        }
+    }
+    best.and_then(|(i, _)| jobs.remove(i))
+}
+
+fn load(job: &Job) -> Option<Image> {
+    let set_progress = |progress| *job.progress.lock().unwrap() = progress;
+    // Stop reading as soon as every handle is dropped or canceled.
+    let on_read = |read, total| {
+        set_progress(LoadProgress::Reading { read, total });
+        !job.is_canceled()
+    };
+    let read;
+    let bytes: &[u8] = match &job.source {
+        ImageSource::Path(path) => {
+            read = read_file(path, on_read).ok()?;
+            &read
+        }
+        ImageSource::Bytes(bytes) => bytes,
+        #[cfg(feature = "network")]
+        ImageSource::Url(url) => {
+            read = read_url(url, on_read).ok()?;
+            &read
+        }
+    };
+    if job.id == SVG {
+        set_progress(LoadProgress::Rasterizing);
+        Image::new_from_svg(bytes)
+    } else {
+        set_progress(LoadProgress::Decoding);
+        Image::new(bytes)
+    }
+}
+
+/// Read a whole file in chunks, calling `on_read(read, total)` after each of them, it returns `false` to stop.
+fn read_file(
+    path: &str,
+    on_read: impl FnMut(u64, Option<u64>) -> bool,
+) -> std::io::Result<Vec<u8>> {
+    let file = File::open(path)?;
+    let total = file.metadata().ok().map(|metadata| metadata.len());
+    read_all(file, total, on_read)
+}
+
+/// Download a whole response body in chunks, `total` comes from the `Content-Length` header.
+#[cfg(feature = "network")]
+fn read_url(url: &str, on_read: impl FnMut(u64, Option<u64>) -> bool) -> std::io::Result<Vec<u8>> {
+    let response = ureq::get(url)
+        .call()
+        .map_err(|e| std::io::Error::new(ErrorKind::Other, e))?;
+    let total = response
+        .header("Content-Length")
+        .and_then(|len| len.parse().ok());
+    read_all(response.into_reader(), total, on_read)
+}
+
+fn read_all(
+    mut reader: impl Read,
+    total: Option<u64>,
+    mut on_read: impl FnMut(u64, Option<u64>) -> bool,
+) -> std::io::Result<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(total.unwrap_or(0).min(64 << 20) as usize);
+    let mut chunk = vec![0; 64 * 1024];
+    let mut go_on = on_read(0, total);
+    while go_on {
+        match reader.read(&mut chunk) {
+            Ok(0) => return Ok(bytes),
+            Ok(n) => {
+                bytes.extend_from_slice(&chunk[..n]);
+                go_on = on_read(bytes.len() as u64, total)
+            }
+            Err(e) if e.kind() == ErrorKind::Interrupted => (),
+            Err(e) => return Err(e),
+        }
+    }
+    Err(ErrorKind::Interrupted.into())
+}
+
+/// Handle to an image queued on an `ImageLoader`, resolved the same way as a `Futurized<LoadProgress, Image>`.
+///
+/// Dropping the handle before it's done cancels the task.
+pub struct ImageTask {
+    id: usize,
+    task: Arc<TaskState>,
+}
+
+impl ImageTask {
+    /// 0 when loading an image, 1 when loading an svg, compare with `Image::type_id`.
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    /// Call `f` with the current progress and whether the task just got resolved,
+    /// `Progress::Error` carries the path or url (or bytes length) of the source that couldn't be loaded.
+    pub fn try_resolve<F: FnOnce(Progress<LoadProgress, Image>, bool)>(&self, f: F) {
+        let mut state = self.task.state.lock().unwrap();
+        if let State::Pending = *state {
+            drop(state);
+            return f(Progress::Current(Some(self.progress())), false);
+        }
+        if let State::Ready(result) = std::mem::replace(&mut *state, State::Resolved) {
+            drop(state);
+            let progress = match result {
+                _ if self.is_canceled() => Progress::Canceled,
+                Ok(image) => Progress::Completed(image),
+                Err(path) => Progress::Error(path.into()),
+            };
+            f(progress, true)
+        }
+    }
+
+    /// Whether the task has been resolved by `try_resolve`.
+    pub fn is_done(&self) -> bool {
+        matches!(*self.task.state.lock().unwrap(), State::Resolved)
+    }
+
+    /// Cancel the task, a queued job is skipped and a running one is reported as `Progress::Canceled`.
+    pub fn cancel(&self) {
+        self.task.canceled.store(true, Ordering::SeqCst)
+    }
+
+    pub fn is_canceled(&self) -> bool {
+        self.task.canceled.load(Ordering::SeqCst)
+    }
+
+    pub fn priority(&self) -> Priority {
+        self.task.priority()
+    }
+
+    /// What the loader is currently doing for this task.
+    pub fn progress(&self) -> LoadProgress {
+        *self.task.progress.lock().unwrap()
+    }
+
+    /// Change the priority while the task is still queued, has no effect once a worker picked it up.
+    pub fn set_priority(&self, priority: Priority) {
+        self.task.priority.store(priority as u8, Ordering::SeqCst)
+    }
+}
+
+impl Drop for ImageTask {
+    fn drop(&mut self) {
+        if !self.is_done() {
+            self.cancel()
+        }
+    }
+}
@@ -0,0 +1,229 @@
+use egui::{pos2, vec2, Color32, InnerResponse, Mesh, Rect, Sense, Shape, TextureId, Ui, Vec2};
+
+/// Above this many quads a tiled nine-slice is stretched instead.
+const MAX_TILES: usize = 4096;
+
+/// Border widths of a nine-slice in image pixels.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Insets {
+    pub left: f32,
+    pub right: f32,
+    pub top: f32,
+    pub bottom: f32,
+}
+
+impl Insets {
+    pub fn same(inset: f32) -> Self {
+        Self::symmetric(inset, inset)
+    }
+
+    /// `x` for left and right, `y` for top and bottom.
+    pub fn symmetric(x: f32, y: f32) -> Self {
+        Self {
+            left: x,
+            right: x,
+            top: y,
+            bottom: y,
+        }
+    }
+}
+
+/// How the edges and center of a nine-slice fill their part.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SliceFill {
+    Stretch,
+    /// Repeat at the native size (times `NineSlice::scale`), the last tile is cut.
+    Tile,
+}
+
+/// Texture drawn as a nine-slice, for skinned buttons and panels.
+///
+/// The corners keep their size, the edges and center stretch or tile to any rect.
+///
+/// ```ignore
+/// let skin = NineSlice::new(image.texture_id(frame), image.size(), Insets::same(12.0));
+/// skin.show(ui, |ui| ui.label("Framed"));
+/// ```
+pub struct NineSlice {
+    texture_id: TextureId,
+    image_size: Vec2,
+    insets: Insets,
+    scale: f32,
+    fill: SliceFill,
+    center: bool,
+    tint: Color32,
+}
+
+impl NineSlice {
+    /// `image_size` is the size of the texture in pixels, e.g. `Image::size`.
+    pub fn new(texture_id: TextureId, image_size: impl Into<Vec2>, insets: Insets) -> Self {
+        Self {
+            texture_id,
+            image_size: image_size.into(),
+            insets,
+            scale: 1.0,
+            fill: SliceFill::Stretch,
+            center: true,
+            tint: Color32::WHITE,
+        }
+    }
+
+    /// Points per image pixel of the borders and tiles, 1.0 by default. `1.0 / pixels_per_point` is pixel exact.
+    pub fn scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// `SliceFill::Stretch` by default.
+    pub fn fill(mut self, fill: SliceFill) -> Self {
+        self.fill = fill;
+        self
+    }
+
+    /// Draw the center part, `false` for a border only.
+    pub fn center(mut self, center: bool) -> Self {
+        self.center = center;
+        self
+    }
+
+    /// Multiply the image colors.
+    pub fn tint(mut self, tint: Color32) -> Self {
+        self.tint = tint;
+        self
+    }
+
+    /// Border widths in points as `(left, top)` and `(right, bottom)`, scaled down if `rect` is too small.
+    fn borders(&self, rect: Rect) -> (Vec2, Vec2) {
+        let min = vec2(self.insets.left, self.insets.top) * self.scale;
+        let max = vec2(self.insets.right, self.insets.bottom) * self.scale;
+        let fit = |size: f32, borders: f32| {
+            if borders > size && borders > 0.0 {
+                size.max(0.0) / borders
+            } else {
+                1.0
+            }
+        };
+        let factor = vec2(
+            fit(rect.width(), min.x + max.x),
+            fit(rect.height(), min.y + max.y),
+        );
+        (min * factor, max * factor)
+    }
+
+    /// The nine-slice filling `rect`.
+    pub fn mesh(&self, rect: Rect) -> Mesh {
+        let (min, max) = self.borders(rect);
+        let size = self.image_size;
+        let xs = [
+            rect.min.x,
+            rect.min.x + min.x,
+            rect.max.x - max.x,
+            rect.max.x,
+        ];
+        let ys = [
+            rect.min.y,
+            rect.min.y + min.y,
+            rect.max.y - max.y,
+            rect.max.y,
+        ];
+        let us = [
+            0.0,
+            self.insets.left / size.x,
+            1.0 - self.insets.right / size.x,
+            1.0,
+        ];
+        let vs = [
+            0.0,
+            self.insets.top / size.y,
+            1.0 - self.insets.bottom / size.y,
+            1.0,
+        ];
+        let tile = match self.fill {
+            SliceFill::Stretch => None,
+            SliceFill::Tile => Some(vec2(
+                (size.x - self.insets.left - self.insets.right) * self.scale,
+                (size.y - self.insets.top - self.insets.bottom) * self.scale,
+            )),
+        };
+        let tile = tile.filter(|tile| {
+            let count = |length: f32, tile: f32| (length / tile).ceil().max(1.0);
+            let quads = count(xs[2] - xs[1], tile.x) * count(ys[3] - ys[0], tile.y)
+                + count(ys[2] - ys[1], tile.y) * count(xs[3] - xs[0], tile.x);
+            tile.x > 0.0 && tile.y > 0.0 && quads <= MAX_TILES as f32
+        });
+
+        let mut mesh = Mesh::with_texture(self.texture_id);
+        for row in 0..3 {
+            for column in 0..3 {
+                if row == 1 && column == 1 && !self.center {
+                    continue;
+                }
+                let x_spans = spans(
+                    [xs[column], xs[column + 1]],
+                    [us[column], us[column + 1]],
+                    tile.filter(|_| column == 1).map(|tile| tile.x),
+                );
+                let y_spans = spans(
+                    [ys[row], ys[row + 1]],
+                    [vs[row], vs[row + 1]],
+                    tile.filter(|_| row == 1).map(|tile| tile.y),
+                );
+                for (y, v) in &y_spans {
+                    for (x, u) in &x_spans {
+                        mesh.add_rect_with_uv(
+                            Rect::from_min_max(pos2(x[0], y[0]), pos2(x[1], y[1])),
+                            Rect::from_min_max(pos2(u[0], v[0]), pos2(u[1], v[1])),
+                            self.tint,
+                        )
+                    }
+                }
+            }
+        }
+        mesh
+    }
+
+    /// Draw into `rect` without allocating.
+    pub fn paint_at(&self, ui: &Ui, rect: Rect) {
+        if ui.clip_rect().intersects(rect) {
+            ui.painter().add(Shape::mesh(self.mesh(rect)));
+        }
+    }
+
+    /// Use as a frame background, the contents are inset by the borders.
+    pub fn show<R>(
+        &self,
+        ui: &mut Ui,
+        add_contents: impl FnOnce(&mut Ui) -> R,
+    ) -> InnerResponse<R> {
+        let background = ui.painter().add(Shape::Noop);
+        let outer = ui.available_rect_before_wrap();
+        let min = vec2(self.insets.left, self.insets.top) * self.scale;
+        let max = vec2(self.insets.right, self.insets.bottom) * self.scale;
+        let mut content_ui = ui.child_ui(
+            Rect::from_min_max(outer.min + min, outer.max - max),
+            *ui.layout(),
+        );
+        let inner = add_contents(&mut content_ui);
+        let rect = Rect::from_min_max(outer.min, content_ui.min_rect().max + max);
+        ui.painter().set(background, Shape::mesh(self.mesh(rect)));
+        let response = ui.allocate_rect(rect, Sense::hover());
+        InnerResponse { inner, response }
+    }
+}
+
+/// Pieces of `pos` with their `uv`, one stretched piece or `tile` long repeats with the last one cut.
+fn spans(pos: [f32; 2], uv: [f32; 2], tile: Option<f32>) -> Vec<([f32; 2], [f32; 2])> {
+    let tile = match tile {
+        Some(tile) => tile,
+        None => return vec![(pos, uv)],
+    };
+    let mut spans = Vec::new();
+    let mut start = pos[0];
+    while start < pos[1] {
+        let end = (start + tile).min(pos[1]);
+        let t = (end - start) / tile;
+        spans.push(([start, end], [uv[0], uv[0] + (uv[1] - uv[0]) * t]));
+        start = end
+    }
+    spans
+}
@@ -0,0 +1,78 @@
+use crate::image_view::fit_image;
+use crate::Fit;
+use egui::{pos2, vec2, Align2, Color32, Mesh, Painter, Rect, Shape, TextureId, Vec2};
+
+/// Above this many tiles `paint_tiled` doubles the tile size.
+const MAX_TILES: usize = 4096;
+
+/// Fill `rect` with a texture repeated every `tile_size` points, the tiles at the edges are cut.
+///
+/// `offset` shifts the tiles (e.g. by the scroll offset for a scrolling background),
+/// use `Image::display_size` times a scale for `tile_size`.
+///
+/// ```ignore
+/// paint_tiled(ui.painter(), texture_id, image.display_size(ppp), Vec2::ZERO, ui.max_rect(), Color32::WHITE);
+/// ```
+pub fn paint_tiled(
+    painter: &Painter,
+    texture_id: TextureId,
+    tile_size: impl Into<Vec2>,
+    offset: Vec2,
+    rect: Rect,
+    tint: Color32,
+) {
+    let mut tile = tile_size.into();
+    if !(rect.is_positive() && tile.x > 0.0 && tile.y > 0.0) {
+        return;
+    }
+    let count = |length: f32, tile: f32| (length / tile).ceil() + 1.0;
+    while count(rect.width(), tile.x) * count(rect.height(), tile.y) > MAX_TILES as f32 {
+        tile *= 2.0
+    }
+
+    let start = rect.min + vec2(offset.x.rem_euclid(tile.x), offset.y.rem_euclid(tile.y)) - tile;
+    let mut mesh = Mesh::with_texture(texture_id);
+    let mut y = start.y;
+    while y < rect.max.y {
+        let mut x = start.x;
+        while x < rect.max.x {
+            let tile_rect = Rect::from_min_size(pos2(x, y), tile);
+            let visible = tile_rect.intersect(rect);
+            if visible.is_positive() {
+                let uv = Rect::from_min_max(
+                    pos2((visible.min.x - x) / tile.x, (visible.min.y - y) / tile.y),
+                    pos2((visible.max.x - x) / tile.x, (visible.max.y - y) / tile.y),
+                );
+                mesh.add_rect_with_uv(visible, uv, tint)
+            }
+            x += tile.x
+        }
+        y += tile.y
+    }
+    painter.add(Shape::mesh(mesh));
+}
+
+/// Draw a texture of `image_size` as background of `rect`, sized by `fit` like `ImageView` and cut to `rect`.
+///
+/// Call it first in a panel or window so the contents are drawn on top.
+///
+/// ```ignore
+/// egui::CentralPanel::default().show(ctx, |ui| {
+///     paint_background(ui.painter(), texture_id, image.size(), ui.max_rect(), Fit::Cover, Align2::CENTER_CENTER, Color32::WHITE);
+///     ..
+/// });
+/// ```
+pub fn paint_background(
+    painter: &Painter,
+    texture_id: TextureId,
+    image_size: impl Into<Vec2>,
+    rect: Rect,
+    fit: Fit,
+    align: Align2,
+    tint: Color32,
+) {
+    let (image_rect, uv) = fit_image(fit, align, image_size.into(), rect);
+    if image_rect.is_positive() {
+        painter.image(texture_id, image_rect, uv, tint)
+    }
+}
@@ -0,0 +1,67 @@
+use crate::Image;
+use egui::{Color32, TextureId};
+
+/// Image decoded at compile time, premultiplied RGBA bytes living in the binary.
+///
+/// Made by `include_static_image!("icons/heart.svg")` (`static-image` feature), which keeps
+/// the decoders out of the final binary. Constructing one is free, even in `const` and `static` items.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StaticImage {
+    width: usize,
+    height: usize,
+    rgba: &'static [u8],
+}
+
+impl StaticImage {
+    /// `rgba` must be `width * height * 4` bytes premultiplied the way egui does (linear space),
+    /// missing pixels are transparent.
+    pub const fn new(width: usize, height: usize, rgba: &'static [u8]) -> Self {
+        Self {
+            width,
+            height,
+            rgba,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Size as float, convenient for `ui.image`.
+    pub fn size(&self) -> (f32, f32) {
+        (self.width as f32, self.height as f32)
+    }
+
+    /// Premultiplied RGBA bytes, row-major.
+    pub fn as_bytes(&self) -> &'static [u8] {
+        self.rgba
+    }
+
+    /// Copy into an `Image`, no decoding involved.
+    pub fn to_image(&self) -> Image {
+        let len = self.width * self.height;
+        // Missing pixels are padded with transparent ones.
+        let pixels = self
+            .rgba
+            .chunks_exact(4)
+            .take(len)
+            .map(|p| Color32::from_rgba_premultiplied(p[0], p[1], p[2], p[3]))
+            .collect();
+        Image::from_parts(self.width, self.height, pixels)
+    }
+
+    /// Upload as a new texture, free it with `frame.tex_allocator().free(texture_id)` when not needed anymore.
+    pub fn texture_id(&self, frame: &mut epi::Frame<'_>) -> TextureId {
+        self.to_image().texture_id(frame)
+    }
+}
+
+impl From<StaticImage> for Image {
+    fn from(image: StaticImage) -> Self {
+        image.to_image()
+    }
+}
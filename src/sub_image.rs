@@ -0,0 +1,73 @@
+use crate::Image;
+use egui::Color32;
+
+/// Borrowed rectangular region of an `Image`, see `Image::view`.
+#[derive(Clone, Copy)]
+pub struct SubImage<'a> {
+    image: &'a Image,
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+}
+
+impl<'a> SubImage<'a> {
+    pub(crate) fn new(
+        image: &'a Image,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+    ) -> Option<SubImage<'a>> {
+        if x + width > image.width() || y + height > image.height() {
+            return None;
+        }
+        Some(SubImage {
+            image,
+            x,
+            y,
+            width,
+            height,
+        })
+    }
+
+    /// Left/top offset of this view inside the parent image.
+    pub fn offset(&self) -> (usize, usize) {
+        (self.x, self.y)
+    }
+
+    /// Width in pixels.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Height in pixels.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Pixel at `x`, `y` relative to this view, `None` if out of bounds.
+    pub fn get_pixel(&self, x: usize, y: usize) -> Option<Color32> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        self.image.get_pixel(self.x + x, self.y + y)
+    }
+
+    /// Iterator over rows of pixels, top to bottom.
+    pub fn rows(&self) -> impl Iterator<Item = &'a [Color32]> + 'a {
+        let (x, width, stride) = (self.x, self.width, self.image.width());
+        let pixels = self.image.pixels();
+        (self.y..self.y + self.height)
+            .map(move |row| &pixels[row * stride + x..row * stride + x + width])
+    }
+
+    /// Copy this region into a new image.
+    pub fn to_image(&self) -> Image {
+        let mut pixels = Vec::with_capacity(self.width * self.height);
+        for row in self.rows() {
+            pixels.extend_from_slice(row)
+        }
+        Image::from_parts(self.width, self.height, pixels)
+    }
+}
@@ -0,0 +1,288 @@
+use crate::Image;
+use asynchron::{Futurize, Futurized, InnerTaskHandle, Progress};
+use image::{codecs::jpeg::JpegDecoder, imageops::FilterType, DynamicImage, ImageDecoder};
+use std::{collections::VecDeque, io::Cursor, path::PathBuf, sync::Arc, time::UNIX_EPOCH};
+
+/// Part of every cache key, bump it when the cached thumbnails or the key change so old files are ignored.
+const CACHE_VERSION: u32 = 1;
+
+/// Where a thumbnail is generated from.
+#[derive(Clone, Debug)]
+pub enum ThumbnailSource {
+    /// Image or .svg file on disk.
+    Path(PathBuf),
+    /// Encoded image bytes (.png, .jpg and .etc).
+    Bytes(Arc<[u8]>),
+}
+
+impl From<PathBuf> for ThumbnailSource {
+    fn from(path: PathBuf) -> Self {
+        ThumbnailSource::Path(path)
+    }
+}
+
+impl From<&str> for ThumbnailSource {
+    fn from(path: &str) -> Self {
+        ThumbnailSource::Path(path.into())
+    }
+}
+
+impl From<Vec<u8>> for ThumbnailSource {
+    fn from(bytes: Vec<u8>) -> Self {
+        ThumbnailSource::Bytes(bytes.into())
+    }
+}
+
+/// How the source is fitted into the thumbnail size.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ThumbnailMode {
+    /// Keep the whole image, thumbnail may be smaller than the requested size on one axis.
+    Fit,
+    /// Fill the requested size exactly, cropping the overflowing part (centered).
+    Fill,
+}
+
+/// Generates fixed size thumbnails on a bounded number of `Futurized` tasks.
+///
+/// Call `poll` every frame, it starts queued requests and hands back the finished ones.
+/// Needs the `image` feature.
+pub struct ThumbnailGenerator {
+    size: (u32, u32),
+    mode: ThumbnailMode,
+    workers: usize,
+    cache_dir: Option<PathBuf>,
+    next_id: usize,
+    queue: VecDeque<(usize, ThumbnailSource)>,
+    running: Vec<Futurized<(), Image>>,
+}
+
+impl ThumbnailGenerator {
+    /// New generator producing thumbnails of `width` x `height` pixels, `ThumbnailMode::Fit` and 4 workers by default.
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            size: (width.max(1), height.max(1)),
+            mode: ThumbnailMode::Fit,
+            workers: 4,
+            cache_dir: None,
+            next_id: 0,
+            queue: VecDeque::new(),
+            running: Vec::new(),
+        }
+    }
+
+    /// How the source is fitted into the thumbnail size.
+    pub fn mode(mut self, mode: ThumbnailMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Maximum number of thumbnails generated at the same time.
+    pub fn workers(mut self, workers: usize) -> Self {
+        self.workers = workers.max(1);
+        self
+    }
+
+    /// Persist generated thumbnails as .png files into `dir` and reuse them on later requests.
+    pub fn cache_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.cache_dir = Some(dir.into());
+        self
+    }
+
+    /// Queue a thumbnail, the returned id is handed back by `poll` once it's done.
+    pub fn request(&mut self, source: impl Into<ThumbnailSource>) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.queue.push_back((id, source.into()));
+        id
+    }
+
+    /// Number of requests queued or in progress.
+    pub fn pending(&self) -> usize {
+        self.queue.len() + self.running.len()
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.pending() == 0
+    }
+
+    /// Drop queued requests and cancel the running ones.
+    pub fn cancel_all(&mut self) {
+        self.queue.clear();
+        for task in &self.running {
+            task.cancel()
+        }
+    }
+
+    /// Start queued requests while there are free workers and collect finished thumbnails as `(id, result)`.
+    pub fn poll(&mut self) -> Vec<(usize, Result<Image, String>)> {
+        while self.running.len() < self.workers {
+            let (id, source) = match self.queue.pop_front() {
+                Some(request) => request,
+                None => break,
+            };
+            let job = Job {
+                source,
+                size: self.size,
+                mode: self.mode,
+                cache_dir: self.cache_dir.clone(),
+            };
+            let task = Futurize::task(id, move |task: InnerTaskHandle| -> Progress<(), Image> {
+                if task.is_canceled() {
+                    return Progress::Canceled;
+                }
+                match job.run() {
+                    Ok(_) if task.is_canceled() => Progress::Canceled,
+                    Ok(thumbnail) => Progress::Completed(thumbnail),
+                    Err(e) => Progress::Error(e.into()),
+                }
+            });
+            task.try_do();
+            self.running.push(task)
+        }
+
+        let mut finished = Vec::new();
+        for task in &self.running {
+            task.try_resolve(|progress, _| match progress {
+                Progress::Completed(thumbnail) => finished.push((task.id(), Ok(thumbnail))),
+                Progress::Error(e) => finished.push((task.id(), Err(e.to_string()))),
+                Progress::Canceled => finished.push((task.id(), Err("canceled".to_string()))),
+                _ => (),
+            })
+        }
+        self.running.retain(|task| !task.is_done());
+        finished
+    }
+}
+
+struct Job {
+    source: ThumbnailSource,
+    size: (u32, u32),
+    mode: ThumbnailMode,
+    cache_dir: Option<PathBuf>,
+}
+
+impl Job {
+    fn run(self) -> Result<Image, String> {
+        let cache_path = self
+            .cache_dir
+            .as_ref()
+            .map(|dir| dir.join(format!("{:016x}.png", self.cache_key())));
+        if let Some(path) = &cache_path {
+            if let Ok(cached) = image::open(path) {
+                return Ok(Image::from(cached));
+            }
+        }
+
+        let thumbnail = match &self.source {
+            ThumbnailSource::Path(path) => {
+                let bytes =
+                    std::fs::read(path).map_err(|e| format!("{}: {}", e, path.display()))?;
+                let is_svg = path
+                    .extension()
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case("svg"));
+                if is_svg {
+                    let svg = Image::new_from_svg(&bytes)
+                        .ok_or_else(|| format!("unable to load {}", path.display()))?;
+                    self.resize(DynamicImage::from(svg))
+                } else {
+                    self.resize(self.decode(&bytes)?)
+                }
+            }
+            ThumbnailSource::Bytes(bytes) => self.resize(self.decode(bytes)?),
+        };
+
+        if let (Some(dir), Some(path)) = (&self.cache_dir, &cache_path) {
+            // A failing cache write shouldn't fail the thumbnail itself.
+            if std::fs::create_dir_all(dir).is_ok() {
+                let _ = thumbnail.save_with_format(path, image::ImageFormat::Png);
+            }
+        }
+        Ok(Image::from(thumbnail))
+    }
+
+    /// Decode `bytes`, JPEGs are DCT-scaled down to the smallest size still covering the thumbnail.
+    fn decode(&self, bytes: &[u8]) -> Result<DynamicImage, String> {
+        if let Ok(image::ImageFormat::Jpeg) = image::guess_format(bytes) {
+            if let Ok(mut decoder) = JpegDecoder::new(Cursor::new(bytes)) {
+                let (width, height) = self.covering_size(decoder.dimensions());
+                let fits_u16 = width <= u16::MAX as u32 && height <= u16::MAX as u32;
+                if fits_u16 && decoder.scale(width as u16, height as u16).is_ok() {
+                    return DynamicImage::from_decoder(decoder).map_err(|e| e.to_string());
+                }
+            }
+        }
+        image::load_from_memory(bytes).map_err(|e| e.to_string())
+    }
+
+    /// Size (keeping aspect ratio) the source has to be scaled to before the final fit or crop.
+    fn covering_size(&self, (width, height): (u32, u32)) -> (u32, u32) {
+        let sx = self.size.0 as f32 / width.max(1) as f32;
+        let sy = self.size.1 as f32 / height.max(1) as f32;
+        let scale = match self.mode {
+            ThumbnailMode::Fit => sx.min(sy),
+            ThumbnailMode::Fill => sx.max(sy),
+        };
+        (
+            (width as f32 * scale).ceil().max(1.0) as u32,
+            (height as f32 * scale).ceil().max(1.0) as u32,
+        )
+    }
+
+    fn resize(&self, image: DynamicImage) -> DynamicImage {
+        let (width, height) = self.size;
+        match self.mode {
+            ThumbnailMode::Fit => image.thumbnail(width, height),
+            ThumbnailMode::Fill => image.resize_to_fill(width, height, FilterType::Triangle),
+        }
+    }
+
+    /// Name of the cached file, hashed with FNV-1a so it stays the same across Rust releases.
+    fn cache_key(&self) -> u64 {
+        let mut hasher = Fnv1a::new();
+        hasher.write(&CACHE_VERSION.to_le_bytes());
+        match &self.source {
+            ThumbnailSource::Path(path) => {
+                hasher.write_bytes(path.to_string_lossy().as_bytes());
+                if let Ok(metadata) = std::fs::metadata(path) {
+                    hasher.write(&metadata.len().to_le_bytes());
+                    let modified = metadata
+                        .modified()
+                        .ok()
+                        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                        .unwrap_or_default();
+                    hasher.write(&modified.as_secs().to_le_bytes());
+                    hasher.write(&modified.subsec_nanos().to_le_bytes());
+                }
+            }
+            ThumbnailSource::Bytes(bytes) => hasher.write_bytes(bytes),
+        }
+        hasher.write(&self.size.0.to_le_bytes());
+        hasher.write(&self.size.1.to_le_bytes());
+        hasher.write(&[match self.mode {
+            ThumbnailMode::Fit => 0,
+            ThumbnailMode::Fill => 1,
+        }]);
+        hasher.0
+    }
+}
+
+/// 64-bit FNV-1a, unlike `DefaultHasher` its output is fixed.
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn new() -> Self {
+        Fnv1a(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ *byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+        }
+    }
+
+    /// Length prefixed, so consecutive fields can't run into each other.
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        self.write(&(bytes.len() as u64).to_le_bytes());
+        self.write(bytes)
+    }
+}
@@ -0,0 +1,319 @@
+use crate::{Image, FULL_UV};
+use egui::{
+    pos2, vec2, Color32, CursorIcon, Mesh, Pos2, Rect, Response, Sense, Shape, TextureId, Ui, Vec2,
+};
+use std::ops::RangeInclusive;
+
+/// Above this many visible pixels the pixelated view falls back to the (filtered) texture.
+const MAX_PIXEL_QUADS: usize = 65_536;
+
+/// Where the image is drawn inside the viewer.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ViewTransform {
+    /// Screen pixels per image pixel, 1.0 is 1:1.
+    pub zoom: f32,
+    /// Offset in points of the image top left corner from the viewer top left corner.
+    pub pan: Vec2,
+}
+
+/// Viewer for inspecting large images and screenshots, drive it by calling `show` every frame.
+///
+/// Mouse wheel (or pinch) zooms around the cursor, dragging pans, double click toggles between fit and 1:1.
+/// Past `pixelated_from` zoom the pixels are drawn as sharp squares when shown with `show_image`,
+/// egui textures are always filtered linearly.
+pub struct ImageViewer {
+    texture_id: TextureId,
+    image_size: Vec2,
+    transform: ViewTransform,
+    zoom_range: RangeInclusive<f32>,
+    pixelated_from: f32,
+    controls: bool,
+    size: Option<Vec2>,
+    /// Fit the image on the next `show`, once the viewer rect is known.
+    fit_pending: bool,
+    rect: Rect,
+    pixels_per_point: f32,
+}
+
+impl ImageViewer {
+    /// `image_size` is the native size of the texture, e.g. `Image::size`.
+    pub fn new(texture_id: TextureId, image_size: impl Into<Vec2>) -> Self {
+        Self {
+            texture_id,
+            image_size: image_size.into(),
+            transform: ViewTransform {
+                zoom: 1.0,
+                pan: Vec2::ZERO,
+            },
+            zoom_range: 0.02..=64.0,
+            pixelated_from: 4.0,
+            controls: true,
+            size: None,
+            fit_pending: true,
+            rect: Rect::NOTHING,
+            pixels_per_point: 1.0,
+        }
+    }
+
+    /// Limits of the zoom, 0.02..=64.0 by default.
+    pub fn zoom_range(mut self, zoom_range: RangeInclusive<f32>) -> Self {
+        self.zoom_range = zoom_range;
+        self
+    }
+
+    /// Zoom from which pixels are drawn as sharp squares by `show_image`, 4.0 by default.
+    pub fn pixelated_from(mut self, zoom: f32) -> Self {
+        self.pixelated_from = zoom;
+        self
+    }
+
+    /// Show the fit / 1:1 buttons and zoom level above the image, `true` by default.
+    pub fn controls(mut self, controls: bool) -> Self {
+        self.controls = controls;
+        self
+    }
+
+    /// Size of the view area, all the available space by default.
+    pub fn size(mut self, size: impl Into<Vec2>) -> Self {
+        self.size = Some(size.into());
+        self
+    }
+
+    /// Show another texture, the view is fitted again if the size differs.
+    pub fn set_texture(&mut self, texture_id: TextureId, image_size: impl Into<Vec2>) {
+        let image_size = image_size.into();
+        if image_size != self.image_size {
+            self.fit_pending = true
+        }
+        self.texture_id = texture_id;
+        self.image_size = image_size;
+    }
+
+    pub fn transform(&self) -> ViewTransform {
+        self.transform
+    }
+
+    pub fn set_transform(&mut self, transform: ViewTransform) {
+        self.transform = transform;
+        self.fit_pending = false
+    }
+
+    /// Current zoom, screen pixels per image pixel.
+    pub fn zoom(&self) -> f32 {
+        self.transform.zoom
+    }
+
+    /// Zoom keeping the image point at the view center in place.
+    pub fn set_zoom(&mut self, zoom: f32) {
+        let center = self.rect.center();
+        self.zoom_around(zoom / self.transform.zoom, center)
+    }
+
+    /// Fit the whole image into the view.
+    pub fn fit(&mut self) {
+        self.fit_pending = true
+    }
+
+    /// One image pixel per screen pixel, centered.
+    pub fn actual_size(&mut self) {
+        self.transform.zoom = self.clamp_zoom(1.0);
+        self.center();
+        self.fit_pending = false
+    }
+
+    /// Screen rect of the whole image (as of the last `show`).
+    pub fn image_rect(&self) -> Rect {
+        Rect::from_min_size(
+            self.rect.min + self.transform.pan,
+            self.image_size * self.points_per_pixel(),
+        )
+    }
+
+    /// Image pixel coordinates (fractional) under a screen position, `None` outside the image.
+    pub fn screen_to_image(&self, pos: Pos2) -> Option<Pos2> {
+        let image_rect = self.image_rect();
+        if !image_rect.contains(pos) || !self.rect.contains(pos) {
+            return None;
+        }
+        let pixel = (pos - image_rect.min) / self.points_per_pixel();
+        Some(pos2(pixel.x, pixel.y))
+    }
+
+    /// Screen position of image pixel coordinates.
+    pub fn image_to_screen(&self, pixel: Pos2) -> Pos2 {
+        self.image_rect().min + pixel.to_vec2() * self.points_per_pixel()
+    }
+
+    /// Show the texture.
+    pub fn show(&mut self, ui: &mut Ui) -> Response {
+        self.show_inner(ui, None)
+    }
+
+    /// Show the texture, switching to sharp pixels from `image` (the texture content) at high zoom.
+    pub fn show_image(&mut self, ui: &mut Ui, image: &Image) -> Response {
+        self.show_inner(ui, Some(image))
+    }
+
+    fn show_inner(&mut self, ui: &mut Ui, image: Option<&Image>) -> Response {
+        if self.controls {
+            self.controls_ui(ui)
+        }
+        let size = self.size.unwrap_or_else(|| ui.available_size());
+        let (rect, response) = ui.allocate_exact_size(size, Sense::click_and_drag());
+        self.layout(ui, rect);
+        self.handle_input(ui, &response, response.hover_pos(), true);
+
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 0.0, ui.visuals().extreme_bg_color);
+        let image_rect = self.image_rect();
+        let pixelated = match image {
+            Some(image) if self.transform.zoom >= self.pixelated_from => {
+                pixel_mesh(image, image_rect, rect)
+            }
+            _ => None,
+        };
+        match pixelated {
+            Some(mesh) => {
+                painter.add(Shape::mesh(mesh));
+            }
+            None => painter.image(self.texture_id, image_rect, FULL_UV, Color32::WHITE),
+        }
+        let cursor = if response.dragged() {
+            CursorIcon::Grabbing
+        } else {
+            CursorIcon::Grab
+        };
+        response.on_hover_cursor(cursor)
+    }
+
+    /// Fit / 1:1 buttons and zoom level.
+    pub(crate) fn controls_ui(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            if ui.button("Fit").clicked() {
+                self.fit()
+            }
+            if ui.button("1:1").clicked() {
+                self.actual_size()
+            }
+            ui.label(format!("{:.0}%", self.transform.zoom * 100.0));
+        });
+    }
+
+    /// Place the view at `rect`, fitting the image if pending.
+    pub(crate) fn layout(&mut self, ui: &Ui, rect: Rect) {
+        self.pixels_per_point = ui.ctx().pixels_per_point();
+        self.rect = rect;
+        if self.fit_pending {
+            self.transform.zoom = self.clamp_zoom(self.fit_zoom());
+            self.center();
+            self.fit_pending = false
+        }
+    }
+
+    /// Double click, wheel zoom around `hover` and (if `pan`) drag to pan.
+    pub(crate) fn handle_input(
+        &mut self,
+        ui: &Ui,
+        response: &Response,
+        hover: Option<Pos2>,
+        pan: bool,
+    ) {
+        if response.double_clicked() {
+            if (self.transform.zoom - 1.0).abs() < 1e-3 {
+                self.fit_pending = true
+            } else {
+                self.actual_size()
+            }
+        }
+        if pan && response.dragged() {
+            self.transform.pan += response.drag_delta()
+        }
+        if let Some(hover) = hover {
+            let input = ui.input();
+            let factor = input.zoom_delta() * (input.scroll_delta.y / 200.0).exp();
+            if (factor - 1.0).abs() > 1e-4 {
+                self.zoom_around(factor, hover)
+            }
+        }
+        self.clamp_pan();
+    }
+
+    fn points_per_pixel(&self) -> f32 {
+        self.transform.zoom / self.pixels_per_point
+    }
+
+    fn fit_zoom(&self) -> f32 {
+        let points =
+            (self.rect.width() / self.image_size.x).min(self.rect.height() / self.image_size.y);
+        if points.is_finite() && points > 0.0 {
+            points * self.pixels_per_point
+        } else {
+            1.0
+        }
+    }
+
+    fn clamp_zoom(&self, zoom: f32) -> f32 {
+        zoom.max(*self.zoom_range.start())
+            .min(*self.zoom_range.end())
+    }
+
+    fn zoom_around(&mut self, factor: f32, anchor: Pos2) {
+        let zoom = self.clamp_zoom(self.transform.zoom * factor);
+        let factor = zoom / self.transform.zoom;
+        let anchor = anchor - self.rect.min;
+        self.transform.pan = anchor - (anchor - self.transform.pan) * factor;
+        self.transform.zoom = zoom;
+        self.fit_pending = false
+    }
+
+    fn center(&mut self) {
+        let size = self.image_size * self.points_per_pixel();
+        self.transform.pan = (self.rect.size() - size) * 0.5
+    }
+
+    /// Keep at least a bit of the image inside the view.
+    fn clamp_pan(&mut self) {
+        let size = self.image_size * self.points_per_pixel();
+        let view = self.rect.size();
+        let margin = vec2(size.x.min(32.0), size.y.min(32.0));
+        let pan = &mut self.transform.pan;
+        pan.x = pan.x.max(margin.x - size.x).min(view.x - margin.x);
+        pan.y = pan.y.max(margin.y - size.y).min(view.y - margin.y);
+    }
+}
+
+/// Visible pixels of `image` as colored squares, `None` if there are too many of them.
+fn pixel_mesh(image: &Image, image_rect: Rect, clip: Rect) -> Option<Mesh> {
+    let (width, height) = (image.width(), image.height());
+    let pixel = vec2(
+        image_rect.width() / width as f32,
+        image_rect.height() / height as f32,
+    );
+    let visible = image_rect.intersect(clip);
+    if width == 0 || height == 0 || visible.width() <= 0.0 || visible.height() <= 0.0 {
+        return Some(Mesh::default());
+    }
+    let x0 = ((visible.min.x - image_rect.min.x) / pixel.x)
+        .floor()
+        .max(0.0) as usize;
+    let y0 = ((visible.min.y - image_rect.min.y) / pixel.y)
+        .floor()
+        .max(0.0) as usize;
+    let x1 = (((visible.max.x - image_rect.min.x) / pixel.x).ceil() as usize).min(width);
+    let y1 = (((visible.max.y - image_rect.min.y) / pixel.y).ceil() as usize).min(height);
+    if (x1 - x0) * (y1 - y0) > MAX_PIXEL_QUADS {
+        return None;
+    }
+    let mut mesh = Mesh::default();
+    for y in y0..y1 {
+        for x in x0..x1 {
+            let color = match image.pixels().get(y * width + x) {
+                Some(color) if color.a() > 0 => *color,
+                _ => continue,
+            };
+            let min = image_rect.min + vec2(x as f32 * pixel.x, y as f32 * pixel.y);
+            mesh.add_colored_rect(Rect::from_min_size(min, pixel), color)
+        }
+    }
+    Some(mesh)
+}